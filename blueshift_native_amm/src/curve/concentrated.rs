@@ -0,0 +1,472 @@
+// concentrated liquidity math, following uniswap v3. prices are stored as q64.64 square roots
+// and every tick is a 0.01% price step: sqrt_price(tick) = 1.0001^(tick / 2)
+
+use pinocchio::program_error::ProgramError;
+
+use crate::{
+    error::PinocchioError,
+    math::{full_mul, mul_div, mul_div_ceil, ONE_Q64},
+};
+
+pub const MIN_TICK: i32 = -443_636;
+pub const MAX_TICK: i32 = 443_636;
+
+// sqrt_price_at_tick(MIN_TICK) and sqrt_price_at_tick(MAX_TICK), the same bounds as orca's
+// whirlpools which use the same q64.64 tick math
+pub const MIN_SQRT_PRICE: u128 = 4_295_048_016;
+pub const MAX_SQRT_PRICE: u128 = 79_226_673_515_401_279_992_447_579_055;
+
+// 1.0001^(2^i / 2) as q32.96, rounded down
+const SQRT_TICK_FACTORS_POSITIVE: [u128; 19] = [
+    0x1000346d6ff11672ae55ad00f,
+    0x100068db8bac710cb295e9e1b,
+    0x1000d1b9c68abe5f76b30fb75,
+    0x1001a37e4a234cb0830516e51,
+    0x100347278ab0e92ada25ab460,
+    0x10068efb00a525480a5d7fdc2,
+    0x100d20a63b4173839df9daaa5,
+    0x101a4c11c742dd7729738df5e,
+    0x1034c35c31f64cfa6dc0d6de4,
+    0x106a34b78c8aaffbf81bed5a3,
+    0x10d72a6a46ccd8bce9ae771b1,
+    0x11b9a258e63928596dc757faa,
+    0x13a2e2bda04f8379f3cd17be5,
+    0x181954be69e0da8fe77f2ab42,
+    0x244c2655d185a029080252877,
+    0x525816eeb9f935b1c616779e8,
+    0x1a7c8d00b551684ff4d31ae065,
+    0x2bd893d0b2df7c97884590c66cd,
+    0x78278e1e19e448cf8b95d2152dccf,
+];
+
+// 1.0001^(-2^i / 2) as q64.64, rounded down
+const SQRT_TICK_FACTORS_NEGATIVE: [u128; 19] = [
+    0xfffcb933bd6fad37,
+    0xfff97272373d4132,
+    0xfff2e50f5f656932,
+    0xffe5caca7e10e4e6,
+    0xffcb9843d60f6159,
+    0xff973b41fa98c081,
+    0xff2ea16466c96a38,
+    0xfe5dee046a99a2a8,
+    0xfcbe86c7900a88ae,
+    0xf987a7253ac41317,
+    0xf3392b0822b70005,
+    0xe7159475a2c29b74,
+    0xd097f3bdfd2022b8,
+    0xa9f746462d870fdf,
+    0x70d869a156d2a1b8,
+    0x31be135f97d08fd9,
+    0x09aa508b5b7a84e1,
+    0x005d6af8dedb8119,
+    0x00002216e584f5fa,
+];
+
+// q64.64 square root price of a tick, rounded down. every factor and product is truncated so
+// it never comes out above the exact price
+pub fn sqrt_price_at_tick(tick: i32) -> Result<u128, ProgramError> {
+    if !(MIN_TICK..=MAX_TICK).contains(&tick) {
+        return Err(PinocchioError::InvalidTickRange.into());
+    }
+
+    let abs_tick = tick.unsigned_abs();
+
+    match tick >= 0 {
+        // the extra 32 bits keep the precision of the large prices, the product of the factors
+        // stays below 2^128 up to MAX_TICK
+        true => {
+            let mut ratio = match abs_tick & 1 {
+                0 => 1 << 96,
+                _ => SQRT_TICK_FACTORS_POSITIVE[0],
+            };
+
+            for (i, factor) in SQRT_TICK_FACTORS_POSITIVE.iter().enumerate().skip(1) {
+                if abs_tick & (1 << i) != 0 {
+                    let (high, low) = full_mul(ratio, *factor);
+                    ratio = (high << 32) | (low >> 96);
+                }
+            }
+
+            Ok(ratio >> 32)
+        }
+        false => {
+            let mut ratio = match abs_tick & 1 {
+                0 => ONE_Q64,
+                _ => SQRT_TICK_FACTORS_NEGATIVE[0],
+            };
+
+            // both factors are at most one, the product fits
+            for (i, factor) in SQRT_TICK_FACTORS_NEGATIVE.iter().enumerate().skip(1) {
+                if abs_tick & (1 << i) != 0 {
+                    ratio = (ratio * factor) >> 64;
+                }
+            }
+
+            Ok(ratio)
+        }
+    }
+}
+
+// greatest tick whose square root price is at or below `sqrt_price`
+pub fn tick_at_sqrt_price(sqrt_price: u128) -> Result<i32, ProgramError> {
+    if !(MIN_SQRT_PRICE..=MAX_SQRT_PRICE).contains(&sqrt_price) {
+        return Err(PinocchioError::InvalidSqrtPrice.into());
+    }
+
+    let (mut low, mut high) = (MIN_TICK, MAX_TICK);
+
+    while low < high {
+        let middle = low + (high - low + 1) / 2;
+
+        if sqrt_price_at_tick(middle)? <= sqrt_price {
+            low = middle;
+        } else {
+            high = middle - 1;
+        }
+    }
+
+    Ok(low)
+}
+
+// amount of x between two square root prices: L * (upper - lower) / (upper * lower)
+pub fn amount_x_delta(
+    sqrt_price_lower: u128,
+    sqrt_price_upper: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> Option<u128> {
+    let difference = sqrt_price_upper.checked_sub(sqrt_price_lower)?;
+
+    match round_up {
+        true => mul_div_ceil(
+            mul_div_ceil(liquidity, difference, sqrt_price_upper)?,
+            ONE_Q64,
+            sqrt_price_lower,
+        ),
+        false => mul_div(
+            mul_div(liquidity, difference, sqrt_price_upper)?,
+            ONE_Q64,
+            sqrt_price_lower,
+        ),
+    }
+}
+
+// amount of y between two square root prices: L * (upper - lower)
+pub fn amount_y_delta(
+    sqrt_price_lower: u128,
+    sqrt_price_upper: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> Option<u128> {
+    let difference = sqrt_price_upper.checked_sub(sqrt_price_lower)?;
+
+    match round_up {
+        true => mul_div_ceil(liquidity, difference, ONE_Q64),
+        false => mul_div(liquidity, difference, ONE_Q64),
+    }
+}
+
+// price after adding `amount` of x, L * P / (L + amount * P) rounded up
+pub fn next_sqrt_price_from_x(sqrt_price: u128, liquidity: u128, amount: u64) -> Option<u128> {
+    let denominator = liquidity.checked_add(mul_div(amount as u128, sqrt_price, ONE_Q64)?)?;
+
+    mul_div_ceil(liquidity, sqrt_price, denominator)
+}
+
+// price after adding `amount` of y, P + amount / L rounded down
+pub fn next_sqrt_price_from_y(sqrt_price: u128, liquidity: u128, amount: u64) -> Option<u128> {
+    sqrt_price.checked_add(mul_div(amount as u128, ONE_Q64, liquidity)?)
+}
+
+pub struct SwapStep {
+    pub sqrt_price: u128,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee: u64,
+}
+
+// swaps within a single liquidity range, stopping at `sqrt_price_target` at the latest.
+// selling x moves the price down, selling y moves it up
+pub fn compute_swap_step(
+    sqrt_price: u128,
+    sqrt_price_target: u128,
+    liquidity: u128,
+    amount_remaining: u64,
+    fee: u16,
+    is_x: bool,
+) -> Result<SwapStep, ProgramError> {
+    let amount_less_fee = mul_div(amount_remaining as u128, 10_000 - fee as u128, 10_000)
+        .ok_or(PinocchioError::MathOverflow)? as u64;
+
+    let (next_sqrt_price, amount_in, amount_out) = match is_x {
+        true => {
+            let max_in = amount_x_delta(sqrt_price_target, sqrt_price, liquidity, true)
+                .ok_or(PinocchioError::MathOverflow)?;
+
+            let next = match amount_less_fee as u128 >= max_in {
+                true => sqrt_price_target,
+                false => next_sqrt_price_from_x(sqrt_price, liquidity, amount_less_fee)
+                    .ok_or(PinocchioError::MathOverflow)?,
+            };
+
+            (
+                next,
+                amount_x_delta(next, sqrt_price, liquidity, true),
+                amount_y_delta(next, sqrt_price, liquidity, false),
+            )
+        }
+        false => {
+            let max_in = amount_y_delta(sqrt_price, sqrt_price_target, liquidity, true)
+                .ok_or(PinocchioError::MathOverflow)?;
+
+            let next = match amount_less_fee as u128 >= max_in {
+                true => sqrt_price_target,
+                false => next_sqrt_price_from_y(sqrt_price, liquidity, amount_less_fee)
+                    .ok_or(PinocchioError::MathOverflow)?,
+            };
+
+            (
+                next,
+                amount_y_delta(sqrt_price, next, liquidity, true),
+                amount_x_delta(sqrt_price, next, liquidity, false),
+            )
+        }
+    };
+
+    let amount_in = amount_in
+        .and_then(|amount| u64::try_from(amount).ok())
+        .ok_or(PinocchioError::MathOverflow)?;
+    let amount_out = amount_out
+        .and_then(|amount| u64::try_from(amount).ok())
+        .ok_or(PinocchioError::MathOverflow)?;
+
+    // a step that stops short of the target used up the whole input, the rest is fee
+    let fee_amount = match next_sqrt_price == sqrt_price_target {
+        true => mul_div_ceil(amount_in as u128, fee as u128, 10_000 - fee as u128)
+            .and_then(|fee| u64::try_from(fee).ok())
+            .ok_or(PinocchioError::MathOverflow)?,
+        false => amount_remaining
+            .checked_sub(amount_in)
+            .ok_or(PinocchioError::MathOverflow)?,
+    };
+
+    Ok(SwapStep {
+        sqrt_price: next_sqrt_price,
+        amount_in,
+        amount_out,
+        fee: fee_amount,
+    })
+}
+
+// token amounts backing `liquidity` between two ticks at the current price. deposits round up
+// and withdrawals round down so the pool never comes up short
+pub fn amounts_for_liquidity(
+    sqrt_price: u128,
+    tick_current: i32,
+    tick_lower: i32,
+    tick_upper: i32,
+    liquidity: u128,
+    round_up: bool,
+) -> Result<(u64, u64), ProgramError> {
+    let sqrt_price_lower = sqrt_price_at_tick(tick_lower)?;
+    let sqrt_price_upper = sqrt_price_at_tick(tick_upper)?;
+
+    let (amount_x, amount_y) = if tick_current < tick_lower {
+        (
+            amount_x_delta(sqrt_price_lower, sqrt_price_upper, liquidity, round_up),
+            Some(0),
+        )
+    } else if tick_current < tick_upper {
+        (
+            amount_x_delta(sqrt_price, sqrt_price_upper, liquidity, round_up),
+            amount_y_delta(sqrt_price_lower, sqrt_price, liquidity, round_up),
+        )
+    } else {
+        (
+            Some(0),
+            amount_y_delta(sqrt_price_lower, sqrt_price_upper, liquidity, round_up),
+        )
+    };
+
+    let to_u64 = |amount: Option<u128>| {
+        amount
+            .and_then(|amount| u64::try_from(amount).ok())
+            .ok_or(PinocchioError::MathOverflow)
+    };
+
+    Ok((to_u64(amount_x)?, to_u64(amount_y)?))
+}
+
+// fees earned per unit of liquidity between two ticks, from the global growth and the growth
+// recorded on the far side of each tick. all of it wraps, only differences are meaningful
+pub fn fee_growth_inside(
+    tick_lower: i32,
+    tick_upper: i32,
+    tick_current: i32,
+    lower_outside: u128,
+    upper_outside: u128,
+    global: u128,
+) -> u128 {
+    let below = match tick_current >= tick_lower {
+        true => lower_outside,
+        false => global.wrapping_sub(lower_outside),
+    };
+
+    let above = match tick_current < tick_upper {
+        true => upper_outside,
+        false => global.wrapping_sub(upper_outside),
+    };
+
+    global.wrapping_sub(below).wrapping_sub(above)
+}
+
+// first tick a swap could cross: at or below the current tick when the price goes down,
+// strictly above it when it goes up
+#[inline(always)]
+pub fn search_start(tick_current: i32, tick_spacing: u16, is_x: bool) -> i32 {
+    let spacing = tick_spacing as i32;
+    let floor = tick_current.div_euclid(spacing) * spacing;
+
+    match is_x {
+        true => floor,
+        false => floor + spacing,
+    }
+}
+
+// checks a position range is made of usable ticks
+pub fn check_tick_range(
+    tick_lower: i32,
+    tick_upper: i32,
+    tick_spacing: u16,
+) -> Result<(), ProgramError> {
+    let spacing = tick_spacing as i32;
+
+    if tick_lower >= tick_upper
+        || tick_lower < MIN_TICK
+        || tick_upper > MAX_TICK
+        || tick_lower % spacing != 0
+        || tick_upper % spacing != 0
+    {
+        return Err(PinocchioError::InvalidTickRange.into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sqrt_price_at_tick_is_rounded_down() {
+        // floor(sqrt(1.0001^tick) * 2^64)
+        assert_eq!(sqrt_price_at_tick(0).unwrap(), ONE_Q64);
+        assert_eq!(sqrt_price_at_tick(1).unwrap(), 18_447_666_387_855_959_850);
+        assert_eq!(sqrt_price_at_tick(-1).unwrap(), 18_445_821_805_675_392_311);
+
+        // the exact bounds are 4_295_048_016.3 and 79_226_673_515_401_279_992_447_579_061.4,
+        // truncating every factor leaves the top of the range a few units low
+        assert_eq!(sqrt_price_at_tick(MIN_TICK).unwrap(), MIN_SQRT_PRICE);
+        assert_eq!(sqrt_price_at_tick(MAX_TICK).unwrap(), MAX_SQRT_PRICE);
+
+        assert!(sqrt_price_at_tick(MIN_TICK - 1).is_err());
+        assert!(sqrt_price_at_tick(MAX_TICK + 1).is_err());
+    }
+
+    #[test]
+    fn tick_round_trip_at_the_bounds() {
+        let ticks = (MIN_TICK..MIN_TICK + 8)
+            .chain(-4..=4)
+            .chain(MAX_TICK - 8..=MAX_TICK);
+
+        for tick in ticks {
+            let sqrt_price = sqrt_price_at_tick(tick).unwrap();
+            assert_eq!(tick_at_sqrt_price(sqrt_price).unwrap(), tick);
+
+            // every price up to the next tick still belongs to this one
+            if tick < MAX_TICK {
+                let next = sqrt_price_at_tick(tick + 1).unwrap();
+                assert!(next > sqrt_price);
+                assert_eq!(tick_at_sqrt_price(next - 1).unwrap(), tick);
+            }
+        }
+
+        assert!(tick_at_sqrt_price(MIN_SQRT_PRICE - 1).is_err());
+        assert!(tick_at_sqrt_price(MAX_SQRT_PRICE + 1).is_err());
+    }
+
+    #[test]
+    fn swap_step_rounds_in_favour_of_the_pool() {
+        let liquidity = 1_000_000_000_000;
+
+        for is_x in [true, false] {
+            let target = sqrt_price_at_tick(if is_x { -1_000 } else { 1_000 }).unwrap();
+
+            let step = compute_swap_step(ONE_Q64, target, liquidity, 1_000_000, 30, is_x).unwrap();
+
+            // stopping short of the target uses up the whole input
+            assert_ne!(step.sqrt_price, target);
+            assert_eq!(step.amount_in + step.fee, 1_000_000);
+
+            // the input is rounded up and the output down on the price range actually crossed
+            let (lower, upper) = match is_x {
+                true => (step.sqrt_price, ONE_Q64),
+                false => (ONE_Q64, step.sqrt_price),
+            };
+            let (amount_in, amount_out) = match is_x {
+                true => (
+                    amount_x_delta(lower, upper, liquidity, false).unwrap(),
+                    amount_y_delta(lower, upper, liquidity, true).unwrap(),
+                ),
+                false => (
+                    amount_y_delta(lower, upper, liquidity, false).unwrap(),
+                    amount_x_delta(lower, upper, liquidity, true).unwrap(),
+                ),
+            };
+            assert!(step.amount_in as u128 >= amount_in);
+            assert!(step.amount_out as u128 <= amount_out);
+
+            // swapping the output straight back without a fee never returns more than went in
+            let back = compute_swap_step(
+                step.sqrt_price,
+                ONE_Q64,
+                liquidity,
+                step.amount_out,
+                0,
+                !is_x,
+            )
+            .unwrap();
+            assert!(back.amount_out <= step.amount_in);
+        }
+    }
+
+    #[test]
+    fn swap_step_stops_at_the_target() {
+        let liquidity = 1_000_000_000_000;
+        let fee = 30;
+
+        for is_x in [true, false] {
+            let target = sqrt_price_at_tick(if is_x { -10 } else { 10 }).unwrap();
+
+            let step =
+                compute_swap_step(ONE_Q64, target, liquidity, u64::MAX / 2, fee, is_x).unwrap();
+            assert_eq!(step.sqrt_price, target);
+
+            let (amount_in, amount_out) = match is_x {
+                true => (
+                    amount_x_delta(target, ONE_Q64, liquidity, true).unwrap(),
+                    amount_y_delta(target, ONE_Q64, liquidity, false).unwrap(),
+                ),
+                false => (
+                    amount_y_delta(ONE_Q64, target, liquidity, true).unwrap(),
+                    amount_x_delta(ONE_Q64, target, liquidity, false).unwrap(),
+                ),
+            };
+            assert_eq!(step.amount_in as u128, amount_in);
+            assert_eq!(step.amount_out as u128, amount_out);
+
+            // the fee on a full step is rounded up too
+            let fee_amount = mul_div_ceil(amount_in, fee as u128, 10_000 - fee as u128).unwrap();
+            assert_eq!(step.fee as u128, fee_amount);
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use core::cmp;
+
+use constant_product_curve::{ConstantProduct, CurveError, LiquidityPair};
+use pinocchio::program_error::ProgramError;
+
+use crate::{
+    curve::SwapQuote,
+    error::PinocchioError,
+    math::{mul_div, mul_div_ceil, sqrt_mul},
+};
+
+// x * y = k, priced by the constant-product-curve crate
+pub fn swap(
+    reserve_x: u64,
+    reserve_y: u64,
+    is_x: bool,
+    amount: u64,
+    min_out: u64,
+    fee: u16,
+) -> Result<SwapQuote, ProgramError> {
+    // the lp supply has no effect on swap pricing so the x reserve is used in its place
+    let mut curve = ConstantProduct::init(reserve_x, reserve_y, reserve_x, fee, None)
+        .map_err(|_| PinocchioError::MathOverflow)?;
+
+    let pair = match is_x {
+        true => LiquidityPair::X,
+        false => LiquidityPair::Y,
+    };
+
+    let swap_result = curve.swap(pair, amount, min_out).map_err(|e| match e {
+        CurveError::SlippageLimitExceeded => PinocchioError::SlipageExceeded,
+        _ => PinocchioError::MathOverflow,
+    })?;
+
+    Ok(SwapQuote {
+        deposit: swap_result.deposit,
+        withdraw: swap_result.withdraw,
+        fee: swap_result.fee,
+    })
+}
+
+// input needed, before the swap fee, for `amount_out` to leave the pool without lowering k.
+// in = reserve_in * out / (reserve_out - out), rounded up
+pub fn swap_exact_out(reserve_in: u64, reserve_out: u64, amount_out: u64) -> Option<u64> {
+    let remaining_out = reserve_out.checked_sub(amount_out)?;
+
+    if reserve_in == 0 || remaining_out == 0 {
+        return None;
+    }
+
+    u64::try_from(mul_div_ceil(
+        reserve_in as u128,
+        amount_out as u128,
+        remaining_out as u128,
+    )?)
+    .ok()
+}
+
+// part of a single sided deposit to swap first so that what is left matches the new pool
+// ratio. with the fee f taken on the input, s solves (1 - f) s^2 + (2 - f) r s - a r = 0:
+// s = (sqrt(((2 - f) r)^2 + 4 (1 - f) a r) - (2 - f) r) / (2 (1 - f)), evaluated as
+// s = 2 a r / (sqrt(((2 - f) r)^2 + 4 (1 - f) a r) + (2 - f) r) so nothing cancels out
+pub fn zap_swap_amount(reserve_in: u64, amount: u64, fee: u16) -> Option<u64> {
+    let (reserve, amount) = (reserve_in as u128, amount as u128);
+
+    // 1 - f and 2 - f in basis points, everything under the root is scaled by 10^8
+    let one_minus_fee = 10_000 - fee as u128;
+    let two_minus_fee = 20_000 - fee as u128;
+
+    let inner = reserve
+        .checked_mul(two_minus_fee * two_minus_fee)?
+        .checked_add(4 * one_minus_fee * 10_000 * amount)?;
+    let root = sqrt_mul(reserve, inner)?;
+
+    u64::try_from(mul_div(
+        2 * 10_000 * amount,
+        reserve,
+        root.checked_add(two_minus_fee * reserve)?,
+    )?)
+    .ok()
+}
+
+// price of x in y and of y in x as q64.64 fixed point numbers
+#[inline(always)]
+pub fn spot_prices(reserve_x: u64, reserve_y: u64) -> Option<(u128, u128)> {
+    if reserve_x == 0 || reserve_y == 0 {
+        return None;
+    }
+
+    Some((
+        ((reserve_y as u128) << 64) / reserve_x as u128,
+        ((reserve_x as u128) << 64) / reserve_y as u128,
+    ))
+}
+
+// lp tokens minted for a deposit, the smaller of the two shares so extra tokens go to the pool
+pub fn deposit(
+    reserve_x: u64,
+    reserve_y: u64,
+    lp_supply: u64,
+    amount_x: u64,
+    amount_y: u64,
+) -> Result<u64, ProgramError> {
+    if reserve_x == 0 && reserve_y == 0 {
+        let product = (amount_x as u128)
+            .checked_mul(amount_y as u128)
+            .ok_or(PinocchioError::MathOverflow)?;
+
+        if product == 0 {
+            return Err(PinocchioError::InvalidMintSupply.into());
+        }
+
+        return Ok(product.isqrt() as u64);
+    }
+
+    if reserve_x == 0 || reserve_y == 0 || lp_supply == 0 {
+        return Err(PinocchioError::InvalidMintSupply.into());
+    };
+
+    let lp_from_x = (amount_x as u128)
+        .checked_mul(lp_supply as u128)
+        .ok_or(PinocchioError::MathOverflow)?
+        .checked_div(reserve_x as u128)
+        .ok_or(PinocchioError::MathOverflow)? as u64;
+
+    let lp_from_y = (amount_y as u128)
+        .checked_mul(lp_supply as u128)
+        .ok_or(PinocchioError::MathOverflow)?
+        .checked_div(reserve_y as u128)
+        .ok_or(PinocchioError::MathOverflow)? as u64;
+
+    Ok(cmp::min(lp_from_x, lp_from_y))
+}
@@ -0,0 +1,441 @@
+// pricing for every curve a pool can be created with. instructions only ever call the
+// functions in this module, which dispatch on the curve stored in the config.
+
+pub mod concentrated;
+pub mod constant_product;
+pub mod stable;
+pub mod weighted;
+
+use pinocchio::program_error::ProgramError;
+
+use crate::{
+    error::PinocchioError,
+    math::mul_div_ceil,
+    state::{Config, CurveType},
+};
+
+// amounts moved by a swap, the fee is charged on the deposit and stays in the pool
+pub struct SwapQuote {
+    pub deposit: u64,
+    pub withdraw: u64,
+    pub fee: u64,
+}
+
+// lp tokens minted by a deposit and the fee it paid on each side. unbalanced deposits into
+// curves that price them themselves pay it like a swap, it stays in the pool
+pub struct DepositQuote {
+    pub lp_amount: u64,
+    pub fee_x: u64,
+    pub fee_y: u64,
+}
+
+// lp tokens of the first deposit that are locked forever, it has to mint more than this
+pub const MINIMUM_LIQUIDITY: u64 = 1000;
+
+pub fn swap(
+    config: &Config,
+    reserve_x: u64,
+    reserve_y: u64,
+    is_x: bool,
+    amount: u64,
+    min_out: u64,
+    timestamp: i64,
+) -> Result<SwapQuote, ProgramError> {
+    let (reserve_in, reserve_out) = match is_x {
+        true => (reserve_x, reserve_y),
+        false => (reserve_y, reserve_x),
+    };
+
+    let quote = match config.curve_type()? {
+        CurveType::ConstantProduct => constant_product::swap(
+            reserve_x,
+            reserve_y,
+            is_x,
+            amount,
+            min_out,
+            config.swap_fee(timestamp),
+        )?,
+        CurveType::Stable => {
+            let (withdraw, fee) = stable::swap(
+                reserve_in,
+                reserve_out,
+                amount,
+                config.swap_fee(timestamp),
+                config.amp(timestamp),
+            )
+            .ok_or(PinocchioError::MathOverflow)?;
+
+            SwapQuote {
+                deposit: amount,
+                withdraw,
+                fee,
+            }
+        }
+        CurveType::Weighted => {
+            let (weight_in, weight_out) = match is_x {
+                true => (config.weight_x(), config.weight_y()),
+                false => (config.weight_y(), config.weight_x()),
+            };
+
+            let (withdraw, fee) = weighted::swap(
+                reserve_in,
+                reserve_out,
+                weight_in,
+                weight_out,
+                amount,
+                config.swap_fee(timestamp),
+            )
+            .ok_or(PinocchioError::MathOverflow)?;
+
+            SwapQuote {
+                deposit: amount,
+                withdraw,
+                fee,
+            }
+        }
+    };
+
+    if quote.deposit == 0 || quote.withdraw == 0 {
+        return Err(PinocchioError::InvalidAmount.into());
+    }
+
+    if quote.withdraw < min_out {
+        return Err(PinocchioError::SlipageExceeded.into());
+    }
+
+    Ok(quote)
+}
+
+// input needed to take exactly `amount_out` out of the pool, the fee is added on top of what
+// the curve needs and every step rounds the input up
+pub fn swap_exact_out(
+    config: &Config,
+    reserve_x: u64,
+    reserve_y: u64,
+    is_x: bool,
+    amount_out: u64,
+    max_in: u64,
+    timestamp: i64,
+) -> Result<SwapQuote, ProgramError> {
+    let (reserve_in, reserve_out) = match is_x {
+        true => (reserve_x, reserve_y),
+        false => (reserve_y, reserve_x),
+    };
+
+    // the pool can never be drained completely
+    if amount_out == 0 || amount_out >= reserve_out {
+        return Err(PinocchioError::InvalidAmount.into());
+    }
+
+    let amount_in = match config.curve_type()? {
+        CurveType::ConstantProduct => {
+            constant_product::swap_exact_out(reserve_in, reserve_out, amount_out)
+        }
+        CurveType::Stable => {
+            stable::swap_exact_out(reserve_in, reserve_out, amount_out, config.amp(timestamp))
+        }
+        CurveType::Weighted => {
+            let (weight_in, weight_out) = match is_x {
+                true => (config.weight_x(), config.weight_y()),
+                false => (config.weight_y(), config.weight_x()),
+            };
+
+            weighted::swap_exact_out(reserve_in, reserve_out, weight_in, weight_out, amount_out)
+        }
+    }
+    .ok_or(PinocchioError::MathOverflow)?;
+
+    let (deposit, fee) = amount_with_fee(amount_in, config.swap_fee(timestamp))
+        .ok_or(PinocchioError::MathOverflow)?;
+
+    if deposit == 0 {
+        return Err(PinocchioError::InvalidAmount.into());
+    }
+
+    if deposit > max_in {
+        return Err(PinocchioError::SlipageExceeded.into());
+    }
+
+    Ok(SwapQuote {
+        deposit,
+        withdraw: amount_out,
+        fee,
+    })
+}
+
+// checks the new reserves are worth at least as much as the old ones under the pool's curve,
+// used to settle flash swaps where the pool is paid after the tokens went out
+pub fn check_invariant(
+    config: &Config,
+    (old_reserve_x, old_reserve_y): (u64, u64),
+    (new_reserve_x, new_reserve_y): (u64, u64),
+    timestamp: i64,
+) -> Result<(), ProgramError> {
+    let holds = match config.curve_type()? {
+        CurveType::ConstantProduct => Some(
+            new_reserve_x as u128 * new_reserve_y as u128
+                >= old_reserve_x as u128 * old_reserve_y as u128,
+        ),
+        CurveType::Stable => {
+            let amp = config.amp(timestamp);
+
+            stable::compute_d(old_reserve_x, old_reserve_y, amp).and_then(|old_d| {
+                Some(stable::compute_d(new_reserve_x, new_reserve_y, amp)? >= old_d)
+            })
+        }
+        CurveType::Weighted => weighted::invariant_holds(
+            old_reserve_x,
+            old_reserve_y,
+            new_reserve_x,
+            new_reserve_y,
+            config.weight_x(),
+            config.weight_y(),
+        ),
+    }
+    .ok_or(PinocchioError::MathOverflow)?;
+
+    if !holds {
+        return Err(PinocchioError::FlashSwapNotRepaid.into());
+    }
+
+    Ok(())
+}
+
+// lp tokens minted for depositing `amount_x` and `amount_y`, and the fee paid on the way
+pub fn deposit(
+    config: &Config,
+    reserve_x: u64,
+    reserve_y: u64,
+    lp_supply: u64,
+    amount_x: u64,
+    amount_y: u64,
+    timestamp: i64,
+) -> Result<DepositQuote, ProgramError> {
+    // the first deposit sets the price, both sides have to be funded
+    if lp_supply == 0 && (amount_x == 0 || amount_y == 0) {
+        return Err(PinocchioError::InvalidMintSupply.into());
+    }
+
+    let (lp_amount, fee_x, fee_y) = match config.curve_type()? {
+        CurveType::ConstantProduct => (
+            constant_product::deposit(reserve_x, reserve_y, lp_supply, amount_x, amount_y)?,
+            0,
+            0,
+        ),
+        CurveType::Stable => stable::deposit(
+            reserve_x,
+            reserve_y,
+            lp_supply,
+            amount_x,
+            amount_y,
+            config.swap_fee(timestamp),
+            config.amp(timestamp),
+        )
+        .ok_or(PinocchioError::MathOverflow)?,
+        // single sided deposits need a price to be priced against, the first one can't be
+        CurveType::Weighted if lp_supply > 0 && (amount_x == 0 || amount_y == 0) => {
+            let (reserve, amount, weight) = match amount_y == 0 {
+                true => (reserve_x, amount_x, config.weight_x()),
+                false => (reserve_y, amount_y, config.weight_y()),
+            };
+
+            let (lp_amount, fee) = weighted::deposit_single_sided(
+                reserve,
+                lp_supply,
+                amount,
+                weight,
+                Config::TOTAL_WEIGHT,
+                config.swap_fee(timestamp),
+            )
+            .ok_or(PinocchioError::MathOverflow)?;
+
+            match amount_y == 0 {
+                true => (lp_amount, fee, 0),
+                false => (lp_amount, 0, fee),
+            }
+        }
+        // a deposit matching the pool's ratio keeps the weights, same as constant product
+        CurveType::Weighted => (
+            constant_product::deposit(reserve_x, reserve_y, lp_supply, amount_x, amount_y)?,
+            0,
+            0,
+        ),
+    };
+
+    if lp_supply == 0 && lp_amount <= MINIMUM_LIQUIDITY {
+        return Err(PinocchioError::InvalidMintSupply.into());
+    }
+
+    Ok(DepositQuote {
+        lp_amount,
+        fee_x,
+        fee_y,
+    })
+}
+
+// lp tokens minted for depositing `amount` of only one side, and the fee paid on the way.
+// constant product pools swap the right part of it first and deposit both sides, the other
+// curves price an unbalanced deposit themselves
+pub fn zap_deposit(
+    config: &Config,
+    reserve_x: u64,
+    reserve_y: u64,
+    lp_supply: u64,
+    is_x: bool,
+    amount: u64,
+    timestamp: i64,
+) -> Result<DepositQuote, ProgramError> {
+    // the first deposit sets the price, it can't be single sided
+    if lp_supply == 0 {
+        return Err(PinocchioError::InvalidMintSupply.into());
+    }
+
+    let (amount_x, amount_y) = match is_x {
+        true => (amount, 0),
+        false => (0, amount),
+    };
+
+    match config.curve_type()? {
+        CurveType::ConstantProduct => {
+            let (reserve_in, reserve_out) = match is_x {
+                true => (reserve_x, reserve_y),
+                false => (reserve_y, reserve_x),
+            };
+
+            let swap_amount =
+                constant_product::zap_swap_amount(reserve_in, amount, config.swap_fee(timestamp))
+                    .ok_or(PinocchioError::MathOverflow)?;
+
+            let quote = swap(
+                config,
+                reserve_x,
+                reserve_y,
+                is_x,
+                swap_amount,
+                0,
+                timestamp,
+            )?;
+
+            // the swapped tokens never leave the vault, they are deposited right back against
+            // the reserves after the swap. the protocol's part of the fee isn't a reserve
+            let protocol_fee = config.protocol_fee_amount(quote.fee)?;
+
+            let reserve_in = reserve_in
+                .checked_add(quote.deposit - protocol_fee)
+                .ok_or(PinocchioError::MathOverflow)?;
+            let reserve_out = reserve_out - quote.withdraw;
+            let remaining = amount - quote.deposit;
+
+            let lp_amount = match is_x {
+                true => constant_product::deposit(
+                    reserve_in,
+                    reserve_out,
+                    lp_supply,
+                    remaining,
+                    quote.withdraw,
+                )?,
+                false => constant_product::deposit(
+                    reserve_out,
+                    reserve_in,
+                    lp_supply,
+                    quote.withdraw,
+                    remaining,
+                )?,
+            };
+
+            let (fee_x, fee_y) = match is_x {
+                true => (quote.fee, 0),
+                false => (0, quote.fee),
+            };
+
+            Ok(DepositQuote {
+                lp_amount,
+                fee_x,
+                fee_y,
+            })
+        }
+        CurveType::Stable | CurveType::Weighted => deposit(
+            config, reserve_x, reserve_y, lp_supply, amount_x, amount_y, timestamp,
+        ),
+    }
+}
+
+// tokens paid out for burning `amount` lp tokens
+pub fn withdraw(
+    config: &Config,
+    reserve_x: u64,
+    reserve_y: u64,
+    lp_supply: u64,
+    amount: u64,
+) -> Result<(u64, u64), ProgramError> {
+    if lp_supply == 0 || amount > lp_supply {
+        return Err(PinocchioError::InvalidMintSupply.into());
+    }
+
+    match config.curve_type()? {
+        // every curve pays out pro-rata, the invariant only matters for pricing
+        CurveType::ConstantProduct | CurveType::Stable | CurveType::Weighted => {
+            pro_rata(reserve_x, reserve_y, lp_supply, amount)
+        }
+    }
+}
+
+// marginal price of x in y and of y in x as q64.64 fixed point numbers
+pub fn spot_prices(
+    config: &Config,
+    reserve_x: u64,
+    reserve_y: u64,
+    timestamp: i64,
+) -> Option<(u128, u128)> {
+    match config.curve_type().ok()? {
+        CurveType::ConstantProduct => constant_product::spot_prices(reserve_x, reserve_y),
+        CurveType::Stable => stable::spot_prices(reserve_x, reserve_y, config.amp(timestamp)),
+        CurveType::Weighted => {
+            weighted::spot_prices(reserve_x, reserve_y, config.weight_x(), config.weight_y())
+        }
+    }
+}
+
+// fee in basis points, rounded up so the pool never undercharges
+#[inline(always)]
+pub fn fee_amount(amount: u64, fee: u16) -> Option<u64> {
+    u64::try_from(mul_div_ceil(amount as u128, fee as u128, 10_000)?).ok()
+}
+
+// smallest amount that still leaves `amount` once `fee_amount` is taken from it, and that fee
+fn amount_with_fee(amount: u64, fee: u16) -> Option<(u64, u64)> {
+    let gross = u64::try_from(mul_div_ceil(
+        amount as u128,
+        10_000,
+        10_000u128.checked_sub(fee as u128)?,
+    )?)
+    .ok()?;
+
+    Some((gross, fee_amount(gross, fee)?))
+}
+
+// the share of each reserve, rounded down in favour of the pool
+fn pro_rata(
+    reserve_x: u64,
+    reserve_y: u64,
+    lp_supply: u64,
+    amount: u64,
+) -> Result<(u64, u64), ProgramError> {
+    if amount == lp_supply {
+        return Ok((reserve_x, reserve_y));
+    }
+
+    let amount_x = (reserve_x as u128)
+        .checked_mul(amount as u128)
+        .ok_or(PinocchioError::MathOverflow)?
+        .checked_div(lp_supply as u128)
+        .ok_or(PinocchioError::MathOverflow)? as u64;
+
+    let amount_y = (reserve_y as u128)
+        .checked_mul(amount as u128)
+        .ok_or(PinocchioError::MathOverflow)?
+        .checked_div(lp_supply as u128)
+        .ok_or(PinocchioError::MathOverflow)? as u64;
+
+    Ok((amount_x, amount_y))
+}
@@ -0,0 +1,420 @@
+// stableswap invariant for two coins, as described in the curve whitepaper:
+// A * n^n * (x + y) + D = A * n^n * D + D^(n+1) / (n^n * x * y)
+// the larger the amplification A, the flatter the curve is around the 1:1 price.
+
+use crate::{
+    curve::fee_amount,
+    math::{full_mul, mul_div, mul_div_ceil, ONE_Q64},
+};
+
+const N_COINS: u128 = 2;
+
+// newton's method converges in a handful of rounds, this only bounds the worst case
+const MAX_ITERATIONS: usize = 255;
+
+// A * n^n
+#[inline(always)]
+fn ann(amp: u64) -> u128 {
+    amp as u128 * N_COINS * N_COINS
+}
+
+// D^(n+1) / (n^n * x * y) rounded down. dividing by the smaller factor first keeps the
+// truncation below a unit, the other way round newton's method can get stuck bouncing around
+// the root
+fn d_product(d: u128, x: u128, y: u128) -> Option<u128> {
+    let (small, large) = (x.min(y), x.max(y));
+
+    mul_div(mul_div(d, d, small * N_COINS)?, d, large * N_COINS)
+}
+
+// same as `d_product` rounded up
+fn d_product_ceil(d: u128, x: u128, y: u128) -> Option<u128> {
+    let (small, large) = (x.min(y), x.max(y));
+
+    mul_div_ceil(mul_div_ceil(d, d, small * N_COINS)?, d, large * N_COINS)
+}
+
+// invariant D for the given reserves, zero for an empty pool. never below the exact value, so a
+// swap quoted against it can't pay out more than the curve allows
+pub fn compute_d(reserve_x: u64, reserve_y: u64, amp: u64) -> Option<u128> {
+    let (x, y) = (reserve_x as u128, reserve_y as u128);
+    let sum = x + y;
+
+    if sum == 0 {
+        return Some(0);
+    }
+
+    // the invariant is undefined once one side is drained
+    if x == 0 || y == 0 || amp == 0 {
+        return None;
+    }
+
+    let ann = ann(amp);
+    let ann_sum = ann.checked_mul(sum)?;
+    let mut d = sum;
+
+    for _ in 0..MAX_ITERATIONS {
+        let d_p = d_product(d, x, y)?;
+
+        let d_prev = d;
+
+        // D = (Ann * S + D_P * n) * D / ((Ann - 1) * D + (n + 1) * D_P)
+        let numerator = ann_sum.checked_add(d_p.checked_mul(N_COINS)?)?;
+        let denominator = (ann - 1)
+            .checked_mul(d)?
+            .checked_add(d_p.checked_mul(N_COINS + 1)?)?;
+
+        d = mul_div(numerator, d, denominator)?;
+
+        if d.abs_diff(d_prev) <= 1 {
+            break;
+        }
+    }
+
+    // the iterations stop within a unit or two of the root, on either side. D is above the root
+    // once (Ann - 1) * D + D_P reaches Ann * S, with D_P rounded down that is never claimed early
+    for _ in 0..MAX_ITERATIONS {
+        if (ann - 1).checked_mul(d)?.checked_add(d_product(d, x, y)?)? >= ann_sum {
+            return Some(d);
+        }
+
+        d = d.checked_add(1)?;
+    }
+
+    None
+}
+
+// reserve of the other coin that keeps the invariant at `d` once one reserve is `reserve`,
+// never below the exact value
+pub fn compute_y(reserve: u64, d: u128, amp: u64) -> Option<u128> {
+    let x = reserve as u128;
+
+    if x == 0 || amp == 0 {
+        return None;
+    }
+
+    let ann = ann(amp);
+
+    // y^2 + (b - D) * y = c, with b = x + D / Ann and c = D^(n+1) / (n^n * x * Ann). b is
+    // rounded down and c up, so a y that solves this is never below the exact one
+    let c = d_product_ceil(d, x, ann)?;
+    let b = x.checked_add(d / ann)?;
+
+    let mut y = d;
+
+    for _ in 0..MAX_ITERATIONS {
+        let y_prev = y;
+
+        // y = (y^2 + c) / (2 * y + b - D)
+        let denominator = y.checked_mul(2)?.checked_add(b)?.checked_sub(d)?;
+
+        y = mul_div(y, y, denominator)?.checked_add(c / denominator)?;
+
+        if y.abs_diff(y_prev) <= 1 {
+            break;
+        }
+    }
+
+    // like D, the root is approached from either side. y is above it once y * (y + b - D) >= c
+    for _ in 0..MAX_ITERATIONS {
+        if let Some(width) = (y + b).checked_sub(d) {
+            let (high, low) = full_mul(y, width);
+
+            if high > 0 || low >= c {
+                return Some(y);
+            }
+        }
+
+        y = y.checked_add(1)?;
+    }
+
+    None
+}
+
+// output amount and fee for swapping `amount` into the pool, the fee is taken on the input.
+// D and the new reserve are both rounded up, so the output is rounded down
+pub fn swap(
+    reserve_in: u64,
+    reserve_out: u64,
+    amount: u64,
+    fee: u16,
+    amp: u64,
+) -> Option<(u64, u64)> {
+    let fee_amount = fee_amount(amount, fee)?;
+    let amount_after_fee = amount.checked_sub(fee_amount)?;
+
+    let d = compute_d(reserve_in, reserve_out, amp)?;
+    let new_reserve_in = reserve_in.checked_add(amount_after_fee)?;
+    let new_reserve_out = compute_y(new_reserve_in, d, amp)?;
+
+    let withdraw = (reserve_out as u128).saturating_sub(new_reserve_out);
+
+    Some((u64::try_from(withdraw).ok()?, fee_amount))
+}
+
+// input needed, before the swap fee, for `amount_out` to leave the pool. rounded up the same
+// way as `swap`
+pub fn swap_exact_out(reserve_in: u64, reserve_out: u64, amount_out: u64, amp: u64) -> Option<u64> {
+    let new_reserve_out = reserve_out.checked_sub(amount_out)?;
+
+    if new_reserve_out == 0 {
+        return None;
+    }
+
+    let d = compute_d(reserve_in, reserve_out, amp)?;
+    let new_reserve_in = compute_y(new_reserve_out, d, amp)?;
+
+    u64::try_from(new_reserve_in.checked_sub(reserve_in as u128)?).ok()
+}
+
+// lp tokens minted for a deposit and the fee paid on each side. like curve, the unbalanced part
+// of a deposit pays the swap fee, otherwise depositing one coin and withdrawing both would be a
+// fee free swap
+pub fn deposit(
+    reserve_x: u64,
+    reserve_y: u64,
+    lp_supply: u64,
+    amount_x: u64,
+    amount_y: u64,
+    fee: u16,
+    amp: u64,
+) -> Option<(u64, u64, u64)> {
+    let new_reserve_x = reserve_x.checked_add(amount_x)?;
+    let new_reserve_y = reserve_y.checked_add(amount_y)?;
+
+    let d1 = compute_d(new_reserve_x, new_reserve_y, amp)?;
+
+    // the first deposit mints the invariant itself
+    if lp_supply == 0 {
+        return Some((u64::try_from(d1).ok()?, 0, 0));
+    }
+
+    let d0 = compute_d(reserve_x, reserve_y, amp)?;
+
+    if d0 == 0 || d1 <= d0 {
+        return Some((0, 0, 0));
+    }
+
+    // fee * n / (4 * (n - 1)) per coin, half the swap fee for two coins
+    let imbalance_fee = fee / 2;
+
+    let (adjusted_x, fee_x) = charge_imbalance(reserve_x, new_reserve_x, d0, d1, imbalance_fee)?;
+    let (adjusted_y, fee_y) = charge_imbalance(reserve_y, new_reserve_y, d0, d1, imbalance_fee)?;
+
+    let d2 = compute_d(adjusted_x, adjusted_y, amp)?;
+
+    if d2 <= d0 {
+        return Some((0, 0, 0));
+    }
+
+    let lp_amount = u64::try_from(mul_div(lp_supply as u128, d2 - d0, d0)?).ok()?;
+
+    Some((lp_amount, fee_x, fee_y))
+}
+
+// fee on the distance of a new reserve from a perfectly balanced deposit, and the reserve left
+// once it is taken off
+fn charge_imbalance(
+    reserve: u64,
+    new_reserve: u64,
+    d0: u128,
+    d1: u128,
+    fee: u16,
+) -> Option<(u64, u64)> {
+    let ideal = mul_div(d1, reserve as u128, d0)?;
+    let difference = u64::try_from(ideal.abs_diff(new_reserve as u128)).ok()?;
+    let fee_amount = fee_amount(difference, fee)?;
+
+    Some((new_reserve.checked_sub(fee_amount)?, fee_amount))
+}
+
+// marginal price of x in y and of y in x as q64.64, from the partial derivatives of the
+// invariant: price_x = (dF/dx) / (dF/dy)
+pub fn spot_prices(reserve_x: u64, reserve_y: u64, amp: u64) -> Option<(u128, u128)> {
+    let d = compute_d(reserve_x, reserve_y, amp)?;
+
+    let (x, y) = (reserve_x as u128, reserve_y as u128);
+    let ann = ann(amp);
+
+    let d_p = d_product(d, x, y)?;
+
+    // x * dF/dx = Ann * x + D_P and y * dF/dy = Ann * y + D_P
+    let gradient_x = ann.checked_mul(x)?.checked_add(d_p)?;
+    let gradient_y = ann.checked_mul(y)?.checked_add(d_p)?;
+
+    Some((
+        mul_div(mul_div(gradient_x, ONE_Q64, gradient_y)?, y, x)?,
+        mul_div(mul_div(gradient_y, ONE_Q64, gradient_x)?, x, y)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AMPS: [u64; 5] = [1, 10, 100, 5_000, 1_000_000];
+
+    // balanced, skewed and extreme pools
+    const RESERVES: [(u64, u64); 7] = [
+        (1_000_000, 1_000_000),
+        (10_000_000_000, 12_000_000_000),
+        (1_000_000, 3),
+        (1_000, 1_000_000_000),
+        (123_456_789, 987_654),
+        (u64::MAX / 2, u64::MAX / 3),
+        (u64::MAX, 1_000_000),
+    ];
+
+    // the same newton iterations in floating point, close enough to the exact curve to tell
+    // which side of it the integer results land on
+    fn exact_d(x: f64, y: f64, amp: f64) -> f64 {
+        let (ann, sum) = (amp * 4.0, x + y);
+        let mut d = sum;
+
+        for _ in 0..MAX_ITERATIONS {
+            let d_p = d * d / (2.0 * x) * d / (2.0 * y);
+            d = (ann * sum + 2.0 * d_p) * d / ((ann - 1.0) * d + 3.0 * d_p);
+        }
+
+        d
+    }
+
+    fn exact_y(x: f64, d: f64, amp: f64) -> f64 {
+        let ann = amp * 4.0;
+        let (b, c) = (x + d / ann, d * d / (2.0 * x) * d / (2.0 * ann));
+        let mut y = d;
+
+        for _ in 0..MAX_ITERATIONS {
+            y = (y * y + c) / (2.0 * y + b - d);
+        }
+
+        y
+    }
+
+    #[test]
+    fn d_converges_above_the_root() {
+        for amp in AMPS {
+            for (x, y) in RESERVES {
+                let d = compute_d(x, y, amp).unwrap();
+                let exact = exact_d(x as f64, y as f64, amp as f64);
+
+                assert!(
+                    d as f64 >= exact * (1.0 - 1e-12),
+                    "amp {amp} reserves {x} {y}"
+                );
+                assert!(
+                    d as f64 <= exact * (1.0 + 1e-12) + 2.0,
+                    "amp {amp} reserves {x} {y}"
+                );
+            }
+        }
+
+        // a balanced pool is exactly on the constant sum line
+        assert_eq!(compute_d(1_000_000, 1_000_000, 100), Some(2_000_000));
+    }
+
+    #[test]
+    fn y_converges_above_the_root() {
+        for amp in AMPS {
+            for (x, y) in RESERVES {
+                let d = compute_d(x, y, amp).unwrap();
+
+                // solving back for the reserve D was computed from can't land below it
+                assert!(compute_y(x, d, amp).unwrap() >= y as u128);
+                assert!(compute_y(y, d, amp).unwrap() >= x as u128);
+            }
+        }
+    }
+
+    #[test]
+    fn empty_and_drained_pools() {
+        assert_eq!(compute_d(0, 0, 100), Some(0));
+        assert_eq!(compute_d(0, 1_000, 100), None);
+        assert_eq!(compute_d(1_000, 1_000, 0), None);
+        assert_eq!(compute_y(0, 2_000, 100), None);
+    }
+
+    #[test]
+    fn swap_rounds_in_favour_of_the_pool() {
+        for amp in AMPS {
+            for (x, y) in RESERVES {
+                for amount in [1, 2, 7, 999, 1_000_000, 77_777_777] {
+                    let Some((out, _)) = swap(x, y, amount, 0, amp) else {
+                        continue;
+                    };
+
+                    // only pools small enough for a float to tell a unit apart
+                    if x.max(y) > 1 << 40 {
+                        continue;
+                    }
+
+                    let d = exact_d(x as f64, y as f64, amp as f64);
+                    let exact = y as f64 - exact_y((x + amount) as f64, d, amp as f64);
+
+                    assert!(
+                        out as f64 <= exact + 1e-6,
+                        "amp {amp} reserves {x} {y} amount {amount}"
+                    );
+
+                    // and the other way round, the input quoted for an output covers it
+                    if out > 0 {
+                        let deposit = swap_exact_out(x, y, out, amp).unwrap();
+                        let exact = exact_y((y - out) as f64, d, amp as f64) - x as f64;
+
+                        assert!(
+                            deposit as f64 >= exact - 1e-6,
+                            "amp {amp} reserves {x} {y} out {out}"
+                        );
+                        assert!(deposit <= amount);
+                    }
+                }
+            }
+        }
+
+        // dust rounds down to nothing
+        assert_eq!(swap(1_000_000, 1_000_000, 1, 0, 100).unwrap().0, 0);
+    }
+
+    #[test]
+    fn swap_never_lowers_the_invariant() {
+        for amp in AMPS {
+            for (x, y) in RESERVES {
+                for amount in [1, 1_000, 999_999, x / 3] {
+                    let Some((out, fee)) = swap(x, y, amount, 30, amp) else {
+                        continue;
+                    };
+
+                    // the fee stays in the pool, so the whole input is added to the reserve
+                    let Some(new_x) = x.checked_add(amount) else {
+                        continue;
+                    };
+
+                    let d0 = exact_d(x as f64, y as f64, amp as f64);
+                    let d1 = exact_d(new_x as f64, (y - out) as f64, amp as f64);
+
+                    assert!(d1 >= d0 * (1.0 - 1e-12), "amp {amp} reserves {x} {y}");
+                    assert_eq!(fee, fee_amount(amount, 30).unwrap());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn unbalanced_deposits_pay_the_fee() {
+        let (x, y, lp_supply) = (1_000_000_000, 1_000_000_000, 2_000_000_000);
+
+        let balanced = deposit(x, y, lp_supply, 1_000_000, 1_000_000, 30, 100).unwrap();
+        let one_sided = deposit(x, y, lp_supply, 2_000_000, 0, 30, 100).unwrap();
+        let one_sided_free = deposit(x, y, lp_supply, 2_000_000, 0, 0, 100).unwrap();
+
+        assert!(one_sided.0 < one_sided_free.0);
+        assert!(one_sided.0 < balanced.0);
+        assert!(balanced.0 <= 2_000_000);
+
+        // a balanced deposit pays nothing, a one sided one pays on both sides of the imbalance
+        assert_eq!((balanced.1, balanced.2), (0, 0));
+        assert_eq!((one_sided_free.1, one_sided_free.2), (0, 0));
+        assert!(one_sided.1 > 0 && one_sided.2 > 0);
+        assert!(one_sided.1 <= fee_amount(2_000_000, 15).unwrap());
+    }
+}
@@ -0,0 +1,302 @@
+// balancer style weighted product: x^wx * y^wy = k. with an 80/20 pool the heavy side moves
+// less as the price changes, so its lps take less impermanent loss.
+
+use crate::{
+    curve::fee_amount,
+    math::{mul_div, mul_div_ceil, mul_q64, pow_q64, ONE_Q64},
+};
+
+// pow_q64 is accurate to about 2^-56, every power is nudged by 2^-48 in the pool's favour
+const POW_ERROR_SHIFT: u32 = 48;
+
+#[inline(always)]
+fn round_up(power: u128) -> u128 {
+    power.saturating_add((power >> POW_ERROR_SHIFT) + 1)
+}
+
+#[inline(always)]
+fn round_down(power: u128) -> u128 {
+    power.saturating_sub((power >> POW_ERROR_SHIFT) + 1)
+}
+
+// output amount and fee for swapping `amount` into the pool, the fee is taken on the input.
+// out = reserve_out * (1 - (reserve_in / (reserve_in + in)) ^ (weight_in / weight_out))
+pub fn swap(
+    reserve_in: u64,
+    reserve_out: u64,
+    weight_in: u16,
+    weight_out: u16,
+    amount: u64,
+    fee: u16,
+) -> Option<(u64, u64)> {
+    let fee_amount = fee_amount(amount, fee)?;
+    let amount_after_fee = amount.checked_sub(fee_amount)?;
+
+    let new_reserve_in = (reserve_in as u128).checked_add(amount_after_fee as u128)?;
+
+    // the base is rounded up and the exponent down, both shrink the output
+    let base = mul_div_ceil(reserve_in as u128, ONE_Q64, new_reserve_in)?;
+    let exponent = mul_div(weight_in as u128, ONE_Q64, weight_out as u128)?;
+
+    let power = round_up(pow_q64(base, exponent)?).min(ONE_Q64);
+
+    let withdraw = mul_div(reserve_out as u128, ONE_Q64 - power, ONE_Q64)?;
+
+    Some((u64::try_from(withdraw).ok()?, fee_amount))
+}
+
+// input needed, before the swap fee, for `amount_out` to leave the pool.
+// in = reserve_in * ((reserve_out / (reserve_out - out)) ^ (weight_out / weight_in) - 1)
+pub fn swap_exact_out(
+    reserve_in: u64,
+    reserve_out: u64,
+    weight_in: u16,
+    weight_out: u16,
+    amount_out: u64,
+) -> Option<u64> {
+    let remaining_out = reserve_out.checked_sub(amount_out)?;
+
+    if remaining_out == 0 {
+        return None;
+    }
+
+    // the base, the exponent and the power are all rounded up, each grows the input
+    let base = mul_div_ceil(reserve_out as u128, ONE_Q64, remaining_out as u128)?;
+    let exponent = mul_div_ceil(weight_out as u128, ONE_Q64, weight_in as u128)?;
+
+    let power = round_up(pow_q64(base, exponent)?);
+
+    let deposit = mul_div_ceil(reserve_in as u128, power.checked_sub(ONE_Q64)?, ONE_Q64)?;
+
+    u64::try_from(deposit).ok()
+}
+
+// lp tokens minted for depositing only one side and the fee paid. the part of the deposit that
+// doesn't match the pool weights is effectively swapped, so it pays the swap fee.
+// lp = supply * (((reserve + in) / reserve) ^ weight - 1)
+pub fn deposit_single_sided(
+    reserve: u64,
+    lp_supply: u64,
+    amount: u64,
+    weight: u16,
+    total_weight: u16,
+    fee: u16,
+) -> Option<(u64, u64)> {
+    let taxable = mul_div_ceil(
+        amount as u128,
+        (total_weight - weight) as u128,
+        total_weight as u128,
+    )?;
+    let fee_amount = fee_amount(u64::try_from(taxable).ok()?, fee)?;
+    let amount_after_fee = amount.checked_sub(fee_amount)?;
+
+    let new_reserve = (reserve as u128).checked_add(amount_after_fee as u128)?;
+
+    // the base and the exponent are rounded down, both shrink the minted amount
+    let base = mul_div(new_reserve, ONE_Q64, reserve as u128)?;
+    let exponent = mul_div(weight as u128, ONE_Q64, total_weight as u128)?;
+
+    let power = round_down(pow_q64(base, exponent)?);
+
+    if power <= ONE_Q64 {
+        return Some((0, fee_amount));
+    }
+
+    let lp_amount = u64::try_from(mul_div(lp_supply as u128, power - ONE_Q64, ONE_Q64)?).ok()?;
+
+    Some((lp_amount, fee_amount))
+}
+
+// whether x^wx * y^wy is at least as large with the new reserves as with the old ones.
+// compared as (new_x / old_x)^wx * (new_y / old_y)^wy >= 1 with every step rounded down
+pub fn invariant_holds(
+    old_reserve_x: u64,
+    old_reserve_y: u64,
+    new_reserve_x: u64,
+    new_reserve_y: u64,
+    weight_x: u16,
+    weight_y: u16,
+) -> Option<bool> {
+    if old_reserve_x == 0 || old_reserve_y == 0 {
+        return None;
+    }
+
+    let total_weight = weight_x as u128 + weight_y as u128;
+
+    let power_x = round_down(pow_q64(
+        mul_div(new_reserve_x as u128, ONE_Q64, old_reserve_x as u128)?,
+        mul_div(weight_x as u128, ONE_Q64, total_weight)?,
+    )?);
+    let power_y = round_down(pow_q64(
+        mul_div(new_reserve_y as u128, ONE_Q64, old_reserve_y as u128)?,
+        mul_div(weight_y as u128, ONE_Q64, total_weight)?,
+    )?);
+
+    Some(mul_q64(power_x, power_y)? >= ONE_Q64)
+}
+
+// marginal price of x in y and of y in x as q64.64, (reserve_y / weight_y) / (reserve_x / weight_x)
+pub fn spot_prices(
+    reserve_x: u64,
+    reserve_y: u64,
+    weight_x: u16,
+    weight_y: u16,
+) -> Option<(u128, u128)> {
+    if reserve_x == 0 || reserve_y == 0 {
+        return None;
+    }
+
+    let value_x = reserve_x as u128 * weight_y as u128;
+    let value_y = reserve_y as u128 * weight_x as u128;
+
+    Some((
+        mul_div(value_y, ONE_Q64, value_x)?,
+        mul_div(value_x, ONE_Q64, value_y)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+
+    // from a 1/99 pool through an even one to a 99/1 pool
+    const WEIGHTS: [(u16, u16); 5] = [
+        (100, 9_900),
+        (2_000, 8_000),
+        (5_000, 5_000),
+        (8_000, 2_000),
+        (9_900, 100),
+    ];
+
+    // kept below 2^53 so the float reference holds them exactly
+    const RESERVES: [(u64, u64); 5] = [
+        (1_000_000, 1_000_000),
+        (1, 1_000_000_000_000),
+        (1_000_000_000_000, 1),
+        (123_456_789, 987_654_321),
+        (1 << 50, 1 << 20),
+    ];
+
+    const AMOUNTS: [u64; 6] = [1, 2, 1_000, 999_999, 1 << 30, 1 << 50];
+
+    fn exact_out(reserve_in: u64, reserve_out: u64, weights: (u16, u16), amount: u64) -> f64 {
+        let ratio = reserve_in as f64 / (reserve_in as f64 + amount as f64);
+
+        reserve_out as f64 * (1.0 - ratio.powf(weights.0 as f64 / weights.1 as f64))
+    }
+
+    #[test]
+    fn swap_never_pays_more_than_the_curve() {
+        for (weight_in, weight_out) in WEIGHTS {
+            for (reserve_in, reserve_out) in RESERVES {
+                for amount in AMOUNTS {
+                    let Some((out, _)) =
+                        swap(reserve_in, reserve_out, weight_in, weight_out, amount, 0)
+                    else {
+                        continue;
+                    };
+
+                    let exact = exact_out(reserve_in, reserve_out, (weight_in, weight_out), amount);
+
+                    assert!(
+                        out as f64 <= exact * (1.0 + 1e-12),
+                        "weights {weight_in}/{weight_out} reserves {reserve_in} {reserve_out} amount {amount}"
+                    );
+
+                    // the pool can never be emptied
+                    assert!(out < reserve_out);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn swap_exact_out_never_charges_less_than_the_curve() {
+        for (weight_in, weight_out) in WEIGHTS {
+            for (reserve_in, reserve_out) in RESERVES {
+                for amount_out in AMOUNTS.map(|amount| amount.min(reserve_out - 1)) {
+                    if amount_out == 0 {
+                        continue;
+                    }
+
+                    let Some(deposit) =
+                        swap_exact_out(reserve_in, reserve_out, weight_in, weight_out, amount_out)
+                    else {
+                        continue;
+                    };
+
+                    let ratio = reserve_out as f64 / (reserve_out - amount_out) as f64;
+                    let exact = reserve_in as f64
+                        * (ratio.powf(weight_out as f64 / weight_in as f64) - 1.0);
+
+                    assert!(
+                        deposit as f64 >= exact * (1.0 - 1e-12),
+                        "weights {weight_in}/{weight_out} reserves {reserve_in} {reserve_out} out {amount_out}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn invariant_holds_for_quoted_swaps_only() {
+        for (weight_in, weight_out) in WEIGHTS {
+            let (out, _) = swap(1_000_000, 1_000_000, weight_in, weight_out, 1_000, 0).unwrap();
+
+            assert_eq!(
+                invariant_holds(
+                    1_000_000,
+                    1_000_000,
+                    1_001_000,
+                    1_000_000 - out,
+                    weight_in,
+                    weight_out
+                ),
+                Some(true)
+            );
+        }
+
+        // taking a couple of units more than the swap quoted breaks it on a balanced pool
+        let (out, _) = swap(1_000_000, 1_000_000, 5_000, 5_000, 1_000, 0).unwrap();
+
+        assert_eq!(
+            invariant_holds(
+                1_000_000,
+                1_000_000,
+                1_001_000,
+                1_000_000 - out - 2,
+                5_000,
+                5_000
+            ),
+            Some(false)
+        );
+        assert_eq!(invariant_holds(0, 1_000_000, 1, 1, 5_000, 5_000), None);
+    }
+
+    #[test]
+    fn single_sided_deposits_never_mint_more_than_the_curve() {
+        for (weight, _) in WEIGHTS {
+            for (reserve, _) in RESERVES {
+                for amount in AMOUNTS {
+                    let lp_supply = 1_000_000_000;
+
+                    let Some((minted, _)) =
+                        deposit_single_sided(reserve, lp_supply, amount, weight, 10_000, 0)
+                    else {
+                        continue;
+                    };
+
+                    let growth = (reserve as f64 + amount as f64) / reserve as f64;
+                    let exact = lp_supply as f64 * (growth.powf(weight as f64 / 10_000.0) - 1.0);
+
+                    assert!(
+                        minted as f64 <= exact * (1.0 + 1e-12),
+                        "weight {weight} reserve {reserve} amount {amount}"
+                    );
+                }
+            }
+        }
+    }
+}
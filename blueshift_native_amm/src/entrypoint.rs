@@ -1,9 +1,108 @@
-use pinocchio::{account_info::AccountInfo, pubkey::Pubkey, ProgramResult};
+use pinocchio::{
+    account_info::AccountInfo, entrypoint, nostd_panic_handler, program_error::ProgramError,
+    pubkey::Pubkey, ProgramResult,
+};
+
+use crate::instructions::{
+    AcceptAuthority, AddFeeTier, CancelOrder, CollectConcentratedProtocolFees, CollectProtocolFees,
+    ConcentratedSwap, CreatePool, DecreaseLiquidity, Deposit, FillOrders, FlashSwap,
+    IncreaseLiquidity, InitializeConcentratedPool, InitializeConfig, InitializeFactory,
+    InitializeOracle, InitializeTickArray, Observe, OpenPosition, PlaceOrder, RampAmp,
+    RenounceAuthority, RouteSwap, SetAuthority, SettleFlashSwap, Skim, StopRampAmp, Swap,
+    SwapExactOut, SyncReserves, UpdateConcentratedState, UpdateDynamicFee, UpdateFee,
+    UpdateProtocolFee, UpdateState, Withdraw, ZapDeposit,
+};
+
+entrypoint!(process_instruction);
+nostd_panic_handler!();
 
 pub fn process_instruction(
     _program_id: &Pubkey,
     accounts: &[AccountInfo],
     instruction_data: &[u8],
 ) -> ProgramResult {
-    Ok(())
+    match instruction_data.split_first() {
+        Some((InitializeConfig::DISCRIMINATOR, data)) => {
+            InitializeConfig::try_from((accounts, data))?.process()
+        }
+        Some((Deposit::DISCRIMINATOR, data)) => Deposit::try_from((accounts, data))?.process(),
+        Some((Swap::DISCRIMINATOR, data)) => Swap::try_from((accounts, data))?.process(),
+        Some((Withdraw::DISCRIMINATOR, data)) => Withdraw::try_from((accounts, data))?.process(),
+        Some((UpdateState::DISCRIMINATOR, data)) => {
+            UpdateState::try_from((accounts, data))?.process()
+        }
+        Some((SetAuthority::DISCRIMINATOR, data)) => {
+            SetAuthority::try_from((accounts, data))?.process()
+        }
+        Some((AcceptAuthority::DISCRIMINATOR, _)) => AcceptAuthority::try_from(accounts)?.process(),
+        Some((RenounceAuthority::DISCRIMINATOR, _)) => {
+            RenounceAuthority::try_from(accounts)?.process()
+        }
+        Some((UpdateFee::DISCRIMINATOR, data)) => UpdateFee::try_from((accounts, data))?.process(),
+        Some((CollectProtocolFees::DISCRIMINATOR, _)) => {
+            CollectProtocolFees::try_from(accounts)?.process()
+        }
+        Some((UpdateProtocolFee::DISCRIMINATOR, data)) => {
+            UpdateProtocolFee::try_from((accounts, data))?.process()
+        }
+        Some((InitializeOracle::DISCRIMINATOR, _)) => {
+            InitializeOracle::try_from(accounts)?.process()
+        }
+        Some((Observe::DISCRIMINATOR, data)) => Observe::try_from((accounts, data))?.process(),
+        Some((RampAmp::DISCRIMINATOR, data)) => RampAmp::try_from((accounts, data))?.process(),
+        Some((StopRampAmp::DISCRIMINATOR, _)) => StopRampAmp::try_from(accounts)?.process(),
+        Some((InitializeConcentratedPool::DISCRIMINATOR, data)) => {
+            InitializeConcentratedPool::try_from((accounts, data))?.process()
+        }
+        Some((InitializeTickArray::DISCRIMINATOR, data)) => {
+            InitializeTickArray::try_from((accounts, data))?.process()
+        }
+        Some((OpenPosition::DISCRIMINATOR, data)) => {
+            OpenPosition::try_from((accounts, data))?.process()
+        }
+        Some((IncreaseLiquidity::DISCRIMINATOR, data)) => {
+            IncreaseLiquidity::try_from((accounts, data))?.process()
+        }
+        Some((DecreaseLiquidity::DISCRIMINATOR, data)) => {
+            DecreaseLiquidity::try_from((accounts, data))?.process()
+        }
+        Some((ConcentratedSwap::DISCRIMINATOR, data)) => {
+            ConcentratedSwap::try_from((accounts, data))?.process()
+        }
+        Some((SwapExactOut::DISCRIMINATOR, data)) => {
+            SwapExactOut::try_from((accounts, data))?.process()
+        }
+        Some((RouteSwap::DISCRIMINATOR, data)) => RouteSwap::try_from((accounts, data))?.process(),
+        Some((FlashSwap::DISCRIMINATOR, data)) => FlashSwap::try_from((accounts, data))?.process(),
+        Some((SettleFlashSwap::DISCRIMINATOR, _)) => SettleFlashSwap::try_from(accounts)?.process(),
+        Some((ZapDeposit::DISCRIMINATOR, data)) => {
+            ZapDeposit::try_from((accounts, data))?.process()
+        }
+        Some((SyncReserves::DISCRIMINATOR, _)) => SyncReserves::try_from(accounts)?.process(),
+        Some((Skim::DISCRIMINATOR, _)) => Skim::try_from(accounts)?.process(),
+        Some((UpdateDynamicFee::DISCRIMINATOR, data)) => {
+            UpdateDynamicFee::try_from((accounts, data))?.process()
+        }
+        Some((InitializeFactory::DISCRIMINATOR, data)) => {
+            InitializeFactory::try_from((accounts, data))?.process()
+        }
+        Some((AddFeeTier::DISCRIMINATOR, data)) => {
+            AddFeeTier::try_from((accounts, data))?.process()
+        }
+        Some((CreatePool::DISCRIMINATOR, data)) => {
+            CreatePool::try_from((accounts, data))?.process()
+        }
+        Some((PlaceOrder::DISCRIMINATOR, data)) => {
+            PlaceOrder::try_from((accounts, data))?.process()
+        }
+        Some((CancelOrder::DISCRIMINATOR, _)) => CancelOrder::try_from(accounts)?.process(),
+        Some((FillOrders::DISCRIMINATOR, _)) => FillOrders::try_from(accounts)?.process(),
+        Some((UpdateConcentratedState::DISCRIMINATOR, data)) => {
+            UpdateConcentratedState::try_from((accounts, data))?.process()
+        }
+        Some((CollectConcentratedProtocolFees::DISCRIMINATOR, _)) => {
+            CollectConcentratedProtocolFees::try_from(accounts)?.process()
+        }
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
 }
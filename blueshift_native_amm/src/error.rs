@@ -14,6 +14,21 @@ pub enum PinocchioError {
     InvalidMintSupply = 0x4,
     InvalidAmount = 0x5,
     SlipageExceeded = 0x6,
+    InvalidPoolState = 0x7,
+    InvalidAuthority = 0x8,
+    InsufficientObservations = 0x9,
+    InvalidCurve = 0xA,
+    InvalidAmp = 0xB,
+    InvalidWeights = 0xC,
+    InvalidTickRange = 0xD,
+    InvalidTickArray = 0xE,
+    InvalidSqrtPrice = 0xF,
+    UnsupportedExtension = 0x10,
+    FlashSwapNotRepaid = 0x11,
+    Expired = 0x12,
+    InvalidFeeTier = 0x13,
+    OrderNotFillable = 0x14,
+    InvalidReferralFee = 0x15,
 }
 
 impl PinocchioError {
@@ -28,6 +43,21 @@ impl PinocchioError {
             PinocchioError::InvalidMintSupply => "Invalid Mint Supply",
             PinocchioError::InvalidAmount => "Invalid Amount",
             PinocchioError::SlipageExceeded => "Slippage Exceeded",
+            PinocchioError::InvalidPoolState => "Pool State Does Not Allow This Instruction",
+            PinocchioError::InvalidAuthority => "Invalid Authority",
+            PinocchioError::InsufficientObservations => "Not Enough Oracle Observations",
+            PinocchioError::InvalidCurve => "Not Supported By The Pool Curve",
+            PinocchioError::InvalidAmp => "Invalid Amplification Ramp",
+            PinocchioError::InvalidWeights => "Invalid Pool Weights",
+            PinocchioError::InvalidTickRange => "Invalid Tick Range",
+            PinocchioError::InvalidTickArray => "Missing Or Invalid Tick Array",
+            PinocchioError::InvalidSqrtPrice => "Invalid Square Root Price",
+            PinocchioError::UnsupportedExtension => "Unsupported Token Extension",
+            PinocchioError::FlashSwapNotRepaid => "Flash Swap Not Repaid",
+            PinocchioError::Expired => "Instruction Expired",
+            PinocchioError::InvalidFeeTier => "Fee Tier Not Allowed",
+            PinocchioError::OrderNotFillable => "Limit Order Price Not Reached",
+            PinocchioError::InvalidReferralFee => "Referral Fee Above The Pool Cap",
         }
     }
 }
@@ -0,0 +1,70 @@
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+use crate::{
+    error::PinocchioError,
+    instructions::{AccountCheck, SignerAccount},
+    state::Config,
+};
+
+pub struct AcceptAuthorityAccounts<'a> {
+    pub pending_authority: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for AcceptAuthorityAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [pending_authority, config] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(pending_authority)?;
+
+        Ok(Self {
+            pending_authority,
+            config,
+        })
+    }
+}
+
+// second step of the authority rotation, signed by the key proposed in SetAuthority
+pub struct AcceptAuthority<'a> {
+    pub accounts: AcceptAuthorityAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for AcceptAuthority<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let accounts = AcceptAuthorityAccounts::try_from(accounts)?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> AcceptAuthority<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &6;
+
+    pub fn process(&self) -> ProgramResult {
+        let mut config = Config::load_mut(self.accounts.config)?;
+
+        // a renounced pool can't be taken over through a stale proposal
+        if config.has_authority().is_none() {
+            return Err(PinocchioError::InvalidAuthority.into());
+        }
+
+        if config.pending_authority() == &Pubkey::default()
+            || config.pending_authority() != self.accounts.pending_authority.key()
+        {
+            return Err(PinocchioError::InvalidAuthority.into());
+        }
+
+        config.set_authority(*self.accounts.pending_authority.key());
+        config.set_pending_authority(Pubkey::default());
+
+        Ok(())
+    }
+}
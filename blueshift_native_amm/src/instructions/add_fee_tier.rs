@@ -0,0 +1,74 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::{
+    instructions::{AccountCheck, SignerAccount},
+    state::Factory,
+};
+
+pub struct AddFeeTierAccounts<'a> {
+    pub authority: &'a AccountInfo,
+    pub factory: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for AddFeeTierAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [authority, factory] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(authority)?;
+
+        Ok(Self { authority, factory })
+    }
+}
+
+pub struct AddFeeTierInstructions {
+    pub fee_tier: u16,
+}
+
+impl<'a> TryFrom<&'a [u8]> for AddFeeTierInstructions {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<u16>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let fee_tier = u16::from_le_bytes([data[0], data[1]]);
+
+        Ok(Self { fee_tier })
+    }
+}
+
+// allows a new fee tier, in basis points, for pools created through the factory
+pub struct AddFeeTier<'a> {
+    pub accounts: AddFeeTierAccounts<'a>,
+    pub instructions: AddFeeTierInstructions,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for AddFeeTier<'a> {
+    type Error = ProgramError;
+
+    fn try_from((accounts, data): (&'a [AccountInfo], &'a [u8])) -> Result<Self, Self::Error> {
+        let accounts = AddFeeTierAccounts::try_from(accounts)?;
+        let instructions = AddFeeTierInstructions::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instructions,
+        })
+    }
+}
+
+impl<'a> AddFeeTier<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &30;
+
+    pub fn process(&self) -> ProgramResult {
+        let mut factory = Factory::load_mut(self.accounts.factory)?;
+
+        factory.check_authority(self.accounts.authority.key())?;
+        factory.add_fee_tier(self.instructions.fee_tier)
+    }
+}
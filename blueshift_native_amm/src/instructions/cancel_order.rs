@@ -0,0 +1,116 @@
+use pinocchio::{
+    account_info::AccountInfo, instruction::Signer, program_error::ProgramError, ProgramResult,
+};
+
+use crate::{
+    instructions::{
+        AccountCheck, AccountClose, AssociatedTokenAccount, AssociatedTokenAccountCheck,
+        ProgramAccount, SignerAccount,
+    },
+    state::LimitOrder,
+    token_interface::{self, CloseAccount, TransferChecked},
+};
+
+pub struct CancelOrderAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub order: &'a AccountInfo,
+
+    pub mint_in: &'a AccountInfo,
+
+    pub order_vault: &'a AccountInfo,
+    pub owner_ata_in: &'a AccountInfo,
+
+    pub token_program_in: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for CancelOrderAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [owner, order, mint_in, order_vault, owner_ata_in, token_program_in] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(owner)?;
+
+        let order_data = LimitOrder::load(order)?;
+        order_data.check_address(order.key())?;
+
+        if order_data.owner() != owner.key() {
+            return Err(ProgramError::IncorrectAuthority);
+        }
+
+        // the escrow is the order's account of its own input mint, any other mint would close an
+        // empty account and the order with the tokens still in the escrow
+        if order_data.mint_in() != mint_in.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        AssociatedTokenAccount::check(order_vault, order, mint_in)?;
+        AssociatedTokenAccount::check(owner_ata_in, owner, mint_in)?;
+        token_interface::check_token_program(token_program_in, mint_in)?;
+
+        Ok(Self {
+            owner,
+            order,
+            mint_in,
+            order_vault,
+            owner_ata_in,
+            token_program_in,
+        })
+    }
+}
+
+// returns whatever is left in the escrow to the owner and closes the order, filled orders are
+// closed the same way to get the rent back
+pub struct CancelOrder<'a> {
+    pub accounts: CancelOrderAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for CancelOrder<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let accounts = CancelOrderAccounts::try_from(accounts)?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> CancelOrder<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &33;
+
+    pub fn process(&self) -> ProgramResult {
+        {
+            let order = LimitOrder::load(self.accounts.order)?;
+            let order_seeds = order.signer_seeds();
+            let signer = [Signer::from(&order_seeds)];
+
+            // the escrow balance rather than the order amount, so nothing is ever stuck in it
+            let amount = token_interface::token_account(self.accounts.order_vault)?.amount();
+
+            if amount > 0 {
+                TransferChecked {
+                    from: self.accounts.order_vault,
+                    mint: self.accounts.mint_in,
+                    to: self.accounts.owner_ata_in,
+                    authority: self.accounts.order,
+                    amount,
+                    decimals: token_interface::mint(self.accounts.mint_in)?.decimals(),
+                    token_program: self.accounts.token_program_in.key(),
+                }
+                .invoke_signed(&signer)?;
+            }
+
+            CloseAccount {
+                account: self.accounts.order_vault,
+                destination: self.accounts.owner,
+                authority: self.accounts.order,
+                token_program: self.accounts.token_program_in.key(),
+            }
+            .invoke_signed(&signer)?;
+        }
+
+        ProgramAccount::close(self.accounts.order, self.accounts.owner)
+    }
+}
@@ -0,0 +1,141 @@
+use pinocchio::{
+    account_info::AccountInfo, instruction::Signer, program_error::ProgramError, ProgramResult,
+};
+
+use crate::{
+    error::PinocchioError,
+    instructions::{AccountCheck, SignerAccount},
+    state::ConcentratedPool,
+    token_interface::{self, TransferChecked},
+};
+
+pub struct CollectConcentratedProtocolFeesAccounts<'a> {
+    pub authority: &'a AccountInfo,
+    pub pool: &'a AccountInfo,
+
+    pub mint_x: &'a AccountInfo,
+    pub mint_y: &'a AccountInfo,
+
+    pub vault_x: &'a AccountInfo,
+    pub vault_y: &'a AccountInfo,
+
+    pub treasury_x: &'a AccountInfo,
+    pub treasury_y: &'a AccountInfo,
+
+    pub token_program_x: &'a AccountInfo,
+    pub token_program_y: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for CollectConcentratedProtocolFeesAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [authority, pool, mint_x, mint_y, vault_x, vault_y, treasury_x, treasury_y, token_program_x, token_program_y] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(authority)?;
+
+        let pool_data = ConcentratedPool::load(pool)?;
+        pool_data.check_address(pool.key())?;
+
+        if pool_data.mint_x() != mint_x.key() || pool_data.mint_y() != mint_y.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if pool_data.vault_x() != vault_x.key() || pool_data.vault_y() != vault_y.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // the treasury accounts can be owned by anyone but must hold the pool mints
+        if token_interface::token_account(treasury_x)?.mint() != pool_data.mint_x()
+            || token_interface::token_account(treasury_y)?.mint() != pool_data.mint_y()
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        token_interface::check_token_program(token_program_x, mint_x)?;
+        token_interface::check_token_program(token_program_y, mint_y)?;
+
+        Ok(Self {
+            authority,
+            pool,
+            mint_x,
+            mint_y,
+            vault_x,
+            vault_y,
+            treasury_x,
+            treasury_y,
+            token_program_x,
+            token_program_y,
+        })
+    }
+}
+
+// sends the protocol share of the concentrated swap fees to the treasury
+pub struct CollectConcentratedProtocolFees<'a> {
+    pub accounts: CollectConcentratedProtocolFeesAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for CollectConcentratedProtocolFees<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let accounts = CollectConcentratedProtocolFeesAccounts::try_from(accounts)?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> CollectConcentratedProtocolFees<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &36;
+
+    pub fn process(&self) -> ProgramResult {
+        let pool = ConcentratedPool::load(self.accounts.pool)?;
+
+        pool.check_authority(self.accounts.authority.key())?;
+
+        let (amount_x, amount_y) = (pool.protocol_fees_x(), pool.protocol_fees_y());
+
+        if amount_x == 0 && amount_y == 0 {
+            return Err(PinocchioError::InvalidAmount.into());
+        }
+
+        let pool_seeds = pool.signer_seeds();
+        let signer = [Signer::from(&pool_seeds)];
+
+        if amount_x > 0 {
+            TransferChecked {
+                from: self.accounts.vault_x,
+                mint: self.accounts.mint_x,
+                to: self.accounts.treasury_x,
+                authority: self.accounts.pool,
+                amount: amount_x,
+                decimals: token_interface::mint(self.accounts.mint_x)?.decimals(),
+                token_program: self.accounts.token_program_x.key(),
+            }
+            .invoke_signed(&signer)?;
+        }
+
+        if amount_y > 0 {
+            TransferChecked {
+                from: self.accounts.vault_y,
+                mint: self.accounts.mint_y,
+                to: self.accounts.treasury_y,
+                authority: self.accounts.pool,
+                amount: amount_y,
+                decimals: token_interface::mint(self.accounts.mint_y)?.decimals(),
+                token_program: self.accounts.token_program_y.key(),
+            }
+            .invoke_signed(&signer)?;
+        }
+
+        drop(pool);
+
+        ConcentratedPool::load_mut(self.accounts.pool)?.clear_protocol_fees();
+
+        Ok(())
+    }
+}
@@ -0,0 +1,142 @@
+use pinocchio::{
+    account_info::AccountInfo, instruction::Signer, program_error::ProgramError, ProgramResult,
+};
+
+use crate::{
+    error::PinocchioError,
+    instructions::{AccountCheck, SignerAccount},
+    state::Config,
+    token_interface::{self, TransferChecked},
+};
+
+pub struct CollectProtocolFeesAccounts<'a> {
+    pub authority: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+
+    pub mint_x: &'a AccountInfo,
+    pub mint_y: &'a AccountInfo,
+
+    pub vault_x: &'a AccountInfo,
+    pub vault_y: &'a AccountInfo,
+
+    pub treasury_x: &'a AccountInfo,
+    pub treasury_y: &'a AccountInfo,
+
+    pub token_program_x: &'a AccountInfo,
+    pub token_program_y: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for CollectProtocolFeesAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [authority, config, mint_x, mint_y, vault_x, vault_y, treasury_x, treasury_y, token_program_x, token_program_y] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(authority)?;
+
+        let config_data = Config::load(config)?;
+        config_data.check_address(config.key())?;
+
+        if config_data.mint_x() != mint_x.key() || config_data.mint_y() != mint_y.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if config_data.mint_x_vault() != vault_x.key()
+            || config_data.mint_y_vault() != vault_y.key()
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // the treasury accounts can be owned by anyone but must hold the pool mints
+        if token_interface::token_account(treasury_x)?.mint() != config_data.mint_x()
+            || token_interface::token_account(treasury_y)?.mint() != config_data.mint_y()
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        token_interface::check_token_program(token_program_x, mint_x)?;
+        token_interface::check_token_program(token_program_y, mint_y)?;
+
+        Ok(Self {
+            authority,
+            config,
+            mint_x,
+            mint_y,
+            vault_x,
+            vault_y,
+            treasury_x,
+            treasury_y,
+            token_program_x,
+            token_program_y,
+        })
+    }
+}
+
+pub struct CollectProtocolFees<'a> {
+    pub accounts: CollectProtocolFeesAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for CollectProtocolFees<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let accounts = CollectProtocolFeesAccounts::try_from(accounts)?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> CollectProtocolFees<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &9;
+
+    pub fn process(&self) -> ProgramResult {
+        let config = Config::load(self.accounts.config)?;
+
+        config.check_authority(self.accounts.authority.key())?;
+
+        let (amount_x, amount_y) = (config.protocol_fees_x(), config.protocol_fees_y());
+
+        if amount_x == 0 && amount_y == 0 {
+            return Err(PinocchioError::InvalidAmount.into());
+        }
+
+        let config_seeds = config.signer_seeds();
+        let signer = [Signer::from(&config_seeds)];
+
+        if amount_x > 0 {
+            TransferChecked {
+                from: self.accounts.vault_x,
+                mint: self.accounts.mint_x,
+                to: self.accounts.treasury_x,
+                authority: self.accounts.config,
+                amount: amount_x,
+                decimals: token_interface::mint(self.accounts.mint_x)?.decimals(),
+                token_program: self.accounts.token_program_x.key(),
+            }
+            .invoke_signed(&signer)?;
+        }
+
+        if amount_y > 0 {
+            TransferChecked {
+                from: self.accounts.vault_y,
+                mint: self.accounts.mint_y,
+                to: self.accounts.treasury_y,
+                authority: self.accounts.config,
+                amount: amount_y,
+                decimals: token_interface::mint(self.accounts.mint_y)?.decimals(),
+                token_program: self.accounts.token_program_y.key(),
+            }
+            .invoke_signed(&signer)?;
+        }
+
+        drop(config);
+
+        Config::load_mut(self.accounts.config)?.clear_protocol_fees();
+
+        Ok(())
+    }
+}
@@ -0,0 +1,374 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::Signer,
+    program_error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    curve::concentrated::{
+        compute_swap_step, search_start, sqrt_price_at_tick, tick_at_sqrt_price, MAX_SQRT_PRICE,
+        MAX_TICK, MIN_SQRT_PRICE, MIN_TICK,
+    },
+    error::PinocchioError,
+    instructions::{AccountCheck, SignerAccount},
+    math::{mul_div, ONE_Q64},
+    state::{AmmState, ConcentratedPool, TickArray},
+    token_interface::{self, TransferChecked},
+};
+
+pub struct ConcentratedSwapAccounts<'a> {
+    pub user: &'a AccountInfo,
+
+    pub pool: &'a AccountInfo,
+
+    pub mint_x: &'a AccountInfo,
+    pub mint_y: &'a AccountInfo,
+
+    pub vault_x: &'a AccountInfo,
+    pub vault_y: &'a AccountInfo,
+
+    pub user_x_ata: &'a AccountInfo,
+    pub user_y_ata: &'a AccountInfo,
+
+    pub token_program_x: &'a AccountInfo,
+    pub token_program_y: &'a AccountInfo,
+
+    // in swap order, starting with the array holding the current tick
+    pub tick_arrays: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for ConcentratedSwapAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [user, pool, mint_x, mint_y, vault_x, vault_y, user_x_ata, user_y_ata, token_program_x, token_program_y, tick_arrays @ ..] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(user)?;
+
+        // the mints and vaults must be the ones the pool was created with
+        let pool_data = ConcentratedPool::load(pool)?;
+        pool_data.check_address(pool.key())?;
+
+        if pool_data.mint_x() != mint_x.key() || pool_data.mint_y() != mint_y.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if pool_data.vault_x() != vault_x.key() || pool_data.vault_y() != vault_y.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        token_interface::check_token_program(token_program_x, mint_x)?;
+        token_interface::check_token_program(token_program_y, mint_y)?;
+
+        if tick_arrays.is_empty() {
+            return Err(PinocchioError::InvalidTickArray.into());
+        }
+
+        for tick_array in tick_arrays {
+            if TickArray::load(tick_array)?.pool() != pool.key() {
+                return Err(PinocchioError::InvalidTickArray.into());
+            }
+        }
+
+        Ok(Self {
+            user,
+            pool,
+            mint_x,
+            mint_y,
+            vault_x,
+            vault_y,
+            user_x_ata,
+            user_y_ata,
+            token_program_x,
+            token_program_y,
+            tick_arrays,
+        })
+    }
+}
+
+pub struct ConcentratedSwapInstructions {
+    pub is_x: bool,
+    pub amount: u64,
+    pub min_out: u64,
+    // the swap stops early once the price reaches it, zero for no limit
+    pub sqrt_price_limit: u128,
+    pub expiration: i64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for ConcentratedSwapInstructions {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len()
+            != size_of::<u8>() + size_of::<u64>() * 2 + size_of::<u128>() + size_of::<i64>()
+        {
+            return Err(ProgramError::InvalidInstructionData);
+        };
+
+        let is_x = match data[0] {
+            0 => false,
+            1 => true,
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+        let amount = u64::from_le_bytes(data[1..9].try_into().unwrap());
+        let min_out = u64::from_le_bytes(data[9..17].try_into().unwrap());
+        let sqrt_price_limit = u128::from_le_bytes(data[17..33].try_into().unwrap());
+        let expiration = i64::from_le_bytes(data[33..41].try_into().unwrap());
+
+        if amount == 0 {
+            return Err(PinocchioError::InvalidAmount.into());
+        }
+
+        Ok(Self {
+            is_x,
+            amount,
+            min_out,
+            sqrt_price_limit,
+            expiration,
+        })
+    }
+}
+
+// swaps against a concentrated pool, crossing ticks as the price leaves each liquidity range
+pub struct ConcentratedSwap<'a> {
+    pub accounts: ConcentratedSwapAccounts<'a>,
+    pub instructions: ConcentratedSwapInstructions,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for ConcentratedSwap<'a> {
+    type Error = ProgramError;
+
+    fn try_from((accounts, data): (&'a [AccountInfo], &'a [u8])) -> Result<Self, Self::Error> {
+        let accounts = ConcentratedSwapAccounts::try_from(accounts)?;
+        let instructions = ConcentratedSwapInstructions::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instructions,
+        })
+    }
+}
+
+impl<'a> ConcentratedSwap<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &20;
+
+    pub fn process(&self) -> ProgramResult {
+        if Clock::get()?.unix_timestamp > self.instructions.expiration {
+            return Err(PinocchioError::Expired.into());
+        }
+
+        let is_x = self.instructions.is_x;
+
+        // the pool is released before the transfers below
+        let (amount_in, amount_out) = {
+            let mut pool = ConcentratedPool::load_mut(self.accounts.pool)?;
+            self.swap_through_ticks(&mut pool)?
+        };
+
+        if amount_out == 0 {
+            return Err(PinocchioError::InvalidAmount.into());
+        }
+
+        if amount_out < self.instructions.min_out {
+            return Err(PinocchioError::SlipageExceeded.into());
+        }
+
+        let pool = ConcentratedPool::load(self.accounts.pool)?;
+        let pool_seeds = pool.signer_seeds();
+        let signer = [Signer::from(&pool_seeds)];
+
+        let (user_from, vault_to, mint_in, token_program_in) = match is_x {
+            true => (
+                self.accounts.user_x_ata,
+                self.accounts.vault_x,
+                self.accounts.mint_x,
+                self.accounts.token_program_x,
+            ),
+            false => (
+                self.accounts.user_y_ata,
+                self.accounts.vault_y,
+                self.accounts.mint_y,
+                self.accounts.token_program_y,
+            ),
+        };
+
+        let (vault_from, user_to, mint_out, token_program_out) = match is_x {
+            true => (
+                self.accounts.vault_y,
+                self.accounts.user_y_ata,
+                self.accounts.mint_y,
+                self.accounts.token_program_y,
+            ),
+            false => (
+                self.accounts.vault_x,
+                self.accounts.user_x_ata,
+                self.accounts.mint_x,
+                self.accounts.token_program_x,
+            ),
+        };
+
+        // transferring the input tokens from the user into the pool
+        TransferChecked {
+            from: user_from,
+            mint: mint_in,
+            to: vault_to,
+            authority: self.accounts.user,
+            amount: amount_in,
+            decimals: token_interface::mint(mint_in)?.decimals(),
+            token_program: token_program_in.key(),
+        }
+        .invoke()?;
+
+        // transferring the output tokens from the pool to the user
+        TransferChecked {
+            from: vault_from,
+            mint: mint_out,
+            to: user_to,
+            authority: self.accounts.pool,
+            amount: amount_out,
+            decimals: token_interface::mint(mint_out)?.decimals(),
+            token_program: token_program_out.key(),
+        }
+        .invoke_signed(&signer)?;
+
+        Ok(())
+    }
+
+    // walks the price through the tick arrays until the input is used up or the limit is hit,
+    // returns the amounts in and out
+    fn swap_through_ticks(&self, pool: &mut ConcentratedPool) -> Result<(u64, u64), ProgramError> {
+        pool.check_state(&[AmmState::Initialized])?;
+
+        let is_x = self.instructions.is_x;
+
+        let fee = pool.fee();
+        let tick_spacing = pool.tick_spacing();
+
+        let mut sqrt_price = pool.sqrt_price();
+        let mut tick_current = pool.tick_current();
+        let mut liquidity = pool.liquidity();
+        let mut fee_growth_global_x = pool.fee_growth_global_x();
+        let mut fee_growth_global_y = pool.fee_growth_global_y();
+
+        // selling x moves the price down, selling y moves it up
+        let sqrt_price_limit = match (self.instructions.sqrt_price_limit, is_x) {
+            (0, true) => MIN_SQRT_PRICE,
+            (0, false) => MAX_SQRT_PRICE,
+            (limit, _) => limit,
+        };
+
+        let valid_limit = match is_x {
+            true => sqrt_price_limit < sqrt_price && sqrt_price_limit >= MIN_SQRT_PRICE,
+            false => sqrt_price_limit > sqrt_price && sqrt_price_limit <= MAX_SQRT_PRICE,
+        };
+
+        if !valid_limit {
+            return Err(PinocchioError::InvalidSqrtPrice.into());
+        }
+
+        let mut amount_remaining = self.instructions.amount;
+        let mut amount_out = 0u64;
+        let mut protocol_fees = 0u64;
+
+        let mut tick_arrays = self.accounts.tick_arrays.iter();
+        let mut tick_array = tick_arrays.next().ok_or(PinocchioError::InvalidTickArray)?;
+
+        while amount_remaining > 0 && sqrt_price != sqrt_price_limit {
+            let search = search_start(tick_current, tick_spacing, is_x);
+
+            // move on to the array holding the next tick, they have to be passed in order
+            while !TickArray::load(tick_array)?.contains(search, tick_spacing) {
+                tick_array = tick_arrays.next().ok_or(PinocchioError::InvalidTickArray)?;
+            }
+
+            let (tick_next, initialized) =
+                TickArray::load(tick_array)?.next_initialized_tick(search, tick_spacing, is_x)?;
+
+            // the outermost arrays reach past the usable tick range
+            let tick_next = tick_next.clamp(MIN_TICK, MAX_TICK);
+            let sqrt_price_next = sqrt_price_at_tick(tick_next)?;
+
+            let sqrt_price_target = match is_x {
+                true => sqrt_price_next.max(sqrt_price_limit),
+                false => sqrt_price_next.min(sqrt_price_limit),
+            };
+
+            let step = compute_swap_step(
+                sqrt_price,
+                sqrt_price_target,
+                liquidity,
+                amount_remaining,
+                fee,
+                is_x,
+            )?;
+
+            amount_remaining = step
+                .amount_in
+                .checked_add(step.fee)
+                .and_then(|spent| amount_remaining.checked_sub(spent))
+                .ok_or(PinocchioError::MathOverflow)?;
+            amount_out = amount_out
+                .checked_add(step.amount_out)
+                .ok_or(PinocchioError::MathOverflow)?;
+
+            // the protocol takes its cut first, the rest is shared by the liquidity in range. both
+            // are paid in the input token
+            let protocol_fee = pool.protocol_fee_amount(step.fee)?;
+            protocol_fees = protocol_fees
+                .checked_add(protocol_fee)
+                .ok_or(PinocchioError::MathOverflow)?;
+
+            if liquidity > 0 {
+                let growth = mul_div((step.fee - protocol_fee) as u128, ONE_Q64, liquidity)
+                    .ok_or(PinocchioError::MathOverflow)?;
+
+                match is_x {
+                    true => fee_growth_global_x = fee_growth_global_x.wrapping_add(growth),
+                    false => fee_growth_global_y = fee_growth_global_y.wrapping_add(growth),
+                }
+            }
+
+            if step.sqrt_price == sqrt_price_next {
+                // the price left the range, the liquidity of the crossed tick flips sides
+                if initialized {
+                    let liquidity_net = TickArray::load_mut(tick_array)?
+                        .tick_mut(tick_next, tick_spacing)?
+                        .cross(fee_growth_global_x, fee_growth_global_y);
+
+                    liquidity = match is_x {
+                        true => liquidity.checked_add_signed(-liquidity_net),
+                        false => liquidity.checked_add_signed(liquidity_net),
+                    }
+                    .ok_or(PinocchioError::MathOverflow)?;
+                }
+
+                tick_current = match is_x {
+                    true => tick_next - 1,
+                    false => tick_next,
+                };
+            } else if step.sqrt_price != sqrt_price {
+                tick_current = tick_at_sqrt_price(step.sqrt_price)?;
+            }
+
+            sqrt_price = step.sqrt_price;
+        }
+
+        pool.set_swap_state(
+            sqrt_price,
+            tick_current,
+            liquidity,
+            fee_growth_global_x,
+            fee_growth_global_y,
+        );
+        pool.accrue_protocol_fees(is_x, protocol_fees)?;
+
+        // whatever is left over after hitting the price limit stays with the user
+        Ok((self.instructions.amount - amount_remaining, amount_out))
+    }
+}
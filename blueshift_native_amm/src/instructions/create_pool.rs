@@ -0,0 +1,148 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::find_program_address,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    error::PinocchioError,
+    instructions::InitializeConfigAccounts,
+    seeds,
+    state::{AmmState, Config, CurveType, Factory},
+};
+
+pub struct CreatePoolAccounts<'a> {
+    // the same accounts as `InitializeConfig`, the signer only pays for the pool
+    pub pool: InitializeConfigAccounts<'a>,
+
+    pub factory: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for CreatePoolAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [pool @ .., factory] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        let pool = InitializeConfigAccounts::try_from(pool)?;
+
+        Factory::load(factory)?.check_address(factory.key())?;
+
+        // the mints are sorted so a pair has a single address per fee tier
+        if pool.mint_x.key() > pool.mint_y.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(Self { pool, factory })
+    }
+}
+
+pub struct CreatePoolInstructions {
+    pub fee_tier: u16,
+}
+
+impl<'a> TryFrom<&'a [u8]> for CreatePoolInstructions {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<u16>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let fee_tier = u16::from_le_bytes([data[0], data[1]]);
+
+        Ok(Self { fee_tier })
+    }
+}
+
+// creates the canonical constant product pool of a pair for one of the factory's fee tiers.
+// anyone can create it, the pool is managed by the factory authority and its fee can't be
+// changed afterwards
+pub struct CreatePool<'a> {
+    pub accounts: CreatePoolAccounts<'a>,
+    pub instructions: CreatePoolInstructions,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for CreatePool<'a> {
+    type Error = ProgramError;
+
+    fn try_from((accounts, data): (&'a [AccountInfo], &'a [u8])) -> Result<Self, Self::Error> {
+        let accounts = CreatePoolAccounts::try_from(accounts)?;
+        let instructions = CreatePoolInstructions::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instructions,
+        })
+    }
+}
+
+impl<'a> CreatePool<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &31;
+
+    pub fn process(&self) -> ProgramResult {
+        let pool = &self.accounts.pool;
+
+        let (authority, protocol_fee) = {
+            let factory = Factory::load(self.accounts.factory)?;
+
+            if !factory.has_fee_tier(self.instructions.fee_tier) {
+                return Err(PinocchioError::InvalidFeeTier.into());
+            }
+
+            (*factory.authority(), factory.protocol_fee())
+        };
+
+        let fee_tier_binding = self.instructions.fee_tier.to_le_bytes();
+
+        let (config_pda, bump) = find_program_address(
+            &seeds::pool(pool.mint_x.key(), pool.mint_y.key(), &fee_tier_binding),
+            &crate::ID,
+        );
+
+        if &config_pda != pool.config.key() {
+            return Err(ProgramError::InvalidAccountData);
+        };
+
+        let bump_binding = [bump];
+        let config_seeds = seeds::pool_signer(
+            pool.mint_x.key(),
+            pool.mint_y.key(),
+            &fee_tier_binding,
+            &bump_binding,
+        );
+
+        let lp_bump = pool.init_pool(&config_seeds)?;
+
+        let mut config_data = Config::load_mut(pool.config)?;
+
+        config_data.set_inner(
+            0,
+            authority,
+            *pool.mint_x.key(),
+            *pool.mint_y.key(),
+            *pool.vault_x.key(),
+            *pool.vault_y.key(),
+            *pool.lp_mint.key(),
+            self.instructions.fee_tier,
+            protocol_fee,
+            bump,
+            lp_bump,
+            AmmState::Initialized as u8,
+            CurveType::ConstantProduct,
+            0,
+            0,
+            0,
+        )?;
+        config_data.set_fee_tier(self.instructions.fee_tier);
+
+        // the accumulators start counting from pool creation
+        config_data.update_price_accumulators(0, 0, &Clock::get()?);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,204 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::Signer,
+    program_error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    error::PinocchioError,
+    instructions::{AccountCheck, SignerAccount},
+    state::{modify_position, AmmState, ConcentratedPool, Position},
+    token_interface::{self, TransferChecked},
+};
+
+pub struct DecreaseLiquidityAccounts<'a> {
+    pub owner: &'a AccountInfo,
+
+    pub pool: &'a AccountInfo,
+    pub position: &'a AccountInfo,
+
+    pub mint_x: &'a AccountInfo,
+    pub mint_y: &'a AccountInfo,
+
+    pub tick_array_lower: &'a AccountInfo,
+    pub tick_array_upper: &'a AccountInfo,
+
+    pub vault_x: &'a AccountInfo,
+    pub vault_y: &'a AccountInfo,
+
+    pub user_x_ata: &'a AccountInfo,
+    pub user_y_ata: &'a AccountInfo,
+
+    pub token_program_x: &'a AccountInfo,
+    pub token_program_y: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for DecreaseLiquidityAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [owner, pool, position, mint_x, mint_y, tick_array_lower, tick_array_upper, vault_x, vault_y, user_x_ata, user_y_ata, token_program_x, token_program_y] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(owner)?;
+
+        // the mints and vaults must be the ones the pool was created with
+        let pool_data = ConcentratedPool::load(pool)?;
+        pool_data.check_address(pool.key())?;
+
+        if pool_data.mint_x() != mint_x.key() || pool_data.mint_y() != mint_y.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if pool_data.vault_x() != vault_x.key() || pool_data.vault_y() != vault_y.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        token_interface::check_token_program(token_program_x, mint_x)?;
+        token_interface::check_token_program(token_program_y, mint_y)?;
+
+        Position::load(position)?.check(pool.key(), owner.key())?;
+
+        Ok(Self {
+            owner,
+            pool,
+            position,
+            mint_x,
+            mint_y,
+            tick_array_lower,
+            tick_array_upper,
+            vault_x,
+            vault_y,
+            user_x_ata,
+            user_y_ata,
+            token_program_x,
+            token_program_y,
+        })
+    }
+}
+
+pub struct DecreaseLiquidityInstructions {
+    pub liquidity: u128,
+    pub min_x: u64,
+    pub min_y: u64,
+    pub expiration: i64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for DecreaseLiquidityInstructions {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<u128>() + size_of::<u64>() * 2 + size_of::<i64>() {
+            return Err(ProgramError::InvalidInstructionData);
+        };
+
+        let liquidity = u128::from_le_bytes(data[0..16].try_into().unwrap());
+        let min_x = u64::from_le_bytes(data[16..24].try_into().unwrap());
+        let min_y = u64::from_le_bytes(data[24..32].try_into().unwrap());
+        let expiration = i64::from_le_bytes(data[32..40].try_into().unwrap());
+
+        if liquidity > i128::MAX as u128 {
+            return Err(PinocchioError::InvalidAmount.into());
+        }
+
+        Ok(Self {
+            liquidity,
+            min_x,
+            min_y,
+            expiration,
+        })
+    }
+}
+
+// removes liquidity from a position and pays out the fees it earned, removing zero liquidity
+// only collects the fees
+pub struct DecreaseLiquidity<'a> {
+    pub accounts: DecreaseLiquidityAccounts<'a>,
+    pub instructions: DecreaseLiquidityInstructions,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for DecreaseLiquidity<'a> {
+    type Error = ProgramError;
+
+    fn try_from((accounts, data): (&'a [AccountInfo], &'a [u8])) -> Result<Self, Self::Error> {
+        let accounts = DecreaseLiquidityAccounts::try_from(accounts)?;
+        let instructions = DecreaseLiquidityInstructions::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instructions,
+        })
+    }
+}
+
+impl<'a> DecreaseLiquidity<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &19;
+
+    pub fn process(&self) -> ProgramResult {
+        if Clock::get()?.unix_timestamp > self.instructions.expiration {
+            return Err(PinocchioError::Expired.into());
+        }
+
+        ConcentratedPool::load(self.accounts.pool)?
+            .check_state(&[AmmState::Initialized, AmmState::WithdrawOnly])?;
+
+        let (amount_x, amount_y) = modify_position(
+            self.accounts.pool,
+            self.accounts.position,
+            self.accounts.tick_array_lower,
+            self.accounts.tick_array_upper,
+            -(self.instructions.liquidity as i128),
+        )?;
+
+        if amount_x < self.instructions.min_x || amount_y < self.instructions.min_y {
+            return Err(PinocchioError::SlipageExceeded.into());
+        }
+
+        // the fees are paid out together with the withdrawn liquidity
+        let (fees_x, fees_y) = Position::load_mut(self.accounts.position)?.take_fees();
+
+        let amount_x = amount_x
+            .checked_add(fees_x)
+            .ok_or(PinocchioError::MathOverflow)?;
+        let amount_y = amount_y
+            .checked_add(fees_y)
+            .ok_or(PinocchioError::MathOverflow)?;
+
+        let pool = ConcentratedPool::load(self.accounts.pool)?;
+        let pool_seeds = pool.signer_seeds();
+        let signer = [Signer::from(&pool_seeds)];
+
+        if amount_x > 0 {
+            TransferChecked {
+                from: self.accounts.vault_x,
+                mint: self.accounts.mint_x,
+                to: self.accounts.user_x_ata,
+                authority: self.accounts.pool,
+                amount: amount_x,
+                decimals: token_interface::mint(self.accounts.mint_x)?.decimals(),
+                token_program: self.accounts.token_program_x.key(),
+            }
+            .invoke_signed(&signer)?;
+        }
+
+        if amount_y > 0 {
+            TransferChecked {
+                from: self.accounts.vault_y,
+                mint: self.accounts.mint_y,
+                to: self.accounts.user_y_ata,
+                authority: self.accounts.pool,
+                amount: amount_y,
+                decimals: token_interface::mint(self.accounts.mint_y)?.decimals(),
+                token_program: self.accounts.token_program_y.key(),
+            }
+            .invoke_signed(&signer)?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,20 +1,20 @@
-use core::cmp;
-
 use pinocchio::{
-    account_info::AccountInfo, program_error::ProgramError, pubkey::find_program_address,
+    account_info::AccountInfo,
+    instruction::Signer,
+    program_error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
     ProgramResult,
 };
-use pinocchio_token::{
-    instructions::{MintTo, Transfer},
-    state::TokenAccount,
-};
 
 use crate::{
+    curve,
     error::PinocchioError,
     instructions::{
-        AccountCheck, AssociatedTokenAccount, AssociatedTokenAccountCheck,
+        optional_account, AccountCheck, AssociatedTokenAccount, AssociatedTokenAccountCheck,
         AssociatedTokenAccountInit, MintInterface, SignerAccount,
     },
+    state::{AmmState, Config, Oracle},
+    token_interface::{self, MintTo, TransferChecked},
 };
 
 pub struct DepositAccounts<'a> {
@@ -33,16 +33,20 @@ pub struct DepositAccounts<'a> {
     pub user_x_ata: &'a AccountInfo,
     pub user_y_ata: &'a AccountInfo,
 
+    pub token_program_x: &'a AccountInfo,
+    pub token_program_y: &'a AccountInfo,
     pub token_program: &'a AccountInfo,
     pub system_program: &'a AccountInfo,
     pub associated_token_program: &'a AccountInfo,
+
+    pub oracle: Option<&'a AccountInfo>,
 }
 
 impl<'a> TryFrom<&'a [AccountInfo]> for DepositAccounts<'a> {
     type Error = ProgramError;
 
     fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
-        let [user, mint_x, mint_y, lp_mint, config, vault_x, vault_y, user_x_ata, user_y_ata, vault_lp, token_program, system_program, associated_token_program] =
+        let [user, mint_x, mint_y, lp_mint, config, vault_x, vault_y, user_x_ata, user_y_ata, vault_lp, token_program_x, token_program_y, token_program, system_program, associated_token_program, remaining @ ..] =
             accounts
         else {
             return Err(ProgramError::InvalidAccountData);
@@ -56,15 +60,29 @@ impl<'a> TryFrom<&'a [AccountInfo]> for DepositAccounts<'a> {
         AssociatedTokenAccount::check(user_x_ata, user, mint_x)?;
         AssociatedTokenAccount::check(user_y_ata, user, mint_y)?;
 
-        let seeds = &[b"lp_mint", config.key().as_ref()];
-        let (expected_lp_mint, _) = find_program_address(seeds, &crate::ID);
+        if mint_x.key() == mint_y.key() {
+            return Err(PinocchioError::IdenticalTokenMints.into());
+        }
 
-        if expected_lp_mint != *lp_mint.key() {
+        token_interface::check_token_program(token_program_x, mint_x)?;
+        token_interface::check_token_program(token_program_y, mint_y)?;
+        token_interface::check_token_program(token_program, lp_mint)?;
+
+        // the mints, vaults and lp mint must be the ones the pool was created with
+        let config_data = Config::load(config)?;
+        config_data.check_address(config.key())?;
+
+        if config_data.mint_x() != mint_x.key()
+            || config_data.mint_y() != mint_y.key()
+            || config_data.lp_mint() != lp_mint.key()
+        {
             return Err(ProgramError::InvalidAccountData);
         }
 
-        if mint_x.key() == mint_y.key() {
-            return Err(PinocchioError::IdenticalTokenMints.into());
+        if config_data.mint_x_vault() != vault_x.key()
+            || config_data.mint_y_vault() != vault_y.key()
+        {
+            return Err(ProgramError::InvalidAccountData);
         }
 
         Ok(Self {
@@ -78,9 +96,12 @@ impl<'a> TryFrom<&'a [AccountInfo]> for DepositAccounts<'a> {
             user_x_ata,
             user_y_ata,
             vault_lp,
+            token_program_x,
+            token_program_y,
             token_program,
             system_program,
             associated_token_program,
+            oracle: optional_account(remaining.first()),
         })
     }
 }
@@ -89,13 +110,14 @@ pub struct DepositInstructions {
     pub mint_x: u64,
     pub mint_y: u64,
     pub min_lp_amount: u64,
+    pub expiration: i64,
 }
 
 impl<'a> TryFrom<&'a [u8]> for DepositInstructions {
     type Error = ProgramError;
 
     fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
-        if data.len() != 24 {
+        if data.len() != 32 {
             return Err(ProgramError::InvalidInstructionData);
         };
 
@@ -108,8 +130,12 @@ impl<'a> TryFrom<&'a [u8]> for DepositInstructions {
         let min_lp_amount = u64::from_le_bytes([
             data[16], data[17], data[18], data[19], data[20], data[21], data[22], data[23],
         ]);
+        let expiration = i64::from_le_bytes([
+            data[24], data[25], data[26], data[27], data[28], data[29], data[30], data[31],
+        ]);
 
-        if mint_x == 0 || mint_y == 0 {
+        // single sided deposits are priced by the pool curve
+        if mint_x == 0 && mint_y == 0 {
             return Err(PinocchioError::InvalidMintAmount.into());
         }
 
@@ -117,6 +143,7 @@ impl<'a> TryFrom<&'a [u8]> for DepositInstructions {
             mint_x,
             mint_y,
             min_lp_amount,
+            expiration,
         })
     }
 }
@@ -124,8 +151,6 @@ impl<'a> TryFrom<&'a [u8]> for DepositInstructions {
 pub struct Deposit<'a> {
     pub accounts: DepositAccounts<'a>,
     pub instructions: DepositInstructions,
-    pub config_bump: u8,
-    pub lp_bump: u8,
 }
 
 impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for Deposit<'a> {
@@ -135,24 +160,7 @@ impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for Deposit<'a> {
         let accounts = DepositAccounts::try_from(accounts)?;
         let instructions = DepositInstructions::try_from(data)?;
 
-        AssociatedTokenAccount::init_if_needed(
-            accounts.vault_x,
-            accounts.mint_x,
-            accounts.user,
-            accounts.user,
-            accounts.system_program,
-            accounts.token_program,
-        )?;
-
-        AssociatedTokenAccount::init_if_needed(
-            accounts.vault_y,
-            accounts.mint_y,
-            accounts.user,
-            accounts.user,
-            accounts.system_program,
-            accounts.token_program,
-        )?;
-
+        // the vaults are created with the pool, only the user's lp account may be missing
         AssociatedTokenAccount::init_if_needed(
             accounts.vault_lp,
             accounts.lp_mint,
@@ -162,27 +170,9 @@ impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for Deposit<'a> {
             accounts.token_program,
         )?;
 
-        let seeds_slice = &[
-            b"config",
-            accounts.mint_x.key().as_ref(),
-            accounts.mint_y.key().as_ref(),
-        ];
-        let (_, config_bump) = find_program_address(seeds_slice, &crate::ID);
-
-        let (_, lp_bump) = find_program_address(
-            &[
-                b"lp_mint",
-                accounts.mint_x.key().as_ref(),
-                accounts.mint_y.key().as_ref(),
-            ],
-            &crate::ID,
-        );
-
         Ok(Self {
             accounts,
             instructions,
-            config_bump,
-            lp_bump,
         })
     }
 }
@@ -190,67 +180,63 @@ impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for Deposit<'a> {
 impl<'a> Deposit<'a> {
     pub const DISCRIMINATOR: &'a u8 = &1;
     pub fn process(&self) -> ProgramResult {
-        // getting the vault datas
-        let vault_x_data = self.accounts.vault_x.try_borrow_data()?;
-        let vault_x = unsafe { TokenAccount::from_bytes_unchecked(&vault_x_data) };
-
-        let vault_y_data = self.accounts.vault_y.try_borrow_data()?;
-        let vault_y = unsafe { TokenAccount::from_bytes_unchecked(&vault_y_data) };
-
-        let vault_lp_data = self.accounts.vault_lp.try_borrow_data()?;
-        let vault_lp = unsafe { TokenAccount::from_bytes_unchecked(&vault_lp_data) };
+        let clock = Clock::get()?;
 
-        if vault_x.owner() != self.accounts.config.key()
-            || vault_y.owner() != self.accounts.config.key()
-        {
-            return Err(PinocchioError::InvalidOwner.into());
+        if clock.unix_timestamp > self.instructions.expiration {
+            return Err(PinocchioError::Expired.into());
         }
 
-        if vault_x.mint() != self.accounts.mint_x.key()
-            || vault_y.mint() != self.accounts.mint_y.key()
-        {
-            return Err(ProgramError::InvalidAccountData);
-        };
+        // token-2022 transfer fees are withheld from what arrives in the vaults, the lp tokens
+        // are only minted for what the pool actually receives
+        let received_x = token_interface::transfer_fee(self.accounts.mint_x, clock.epoch)?
+            .amount_received(self.instructions.mint_x)
+            .ok_or(PinocchioError::MathOverflow)?;
+        let received_y = token_interface::transfer_fee(self.accounts.mint_y, clock.epoch)?
+            .amount_received(self.instructions.mint_y)
+            .ok_or(PinocchioError::MathOverflow)?;
+
+        let lp_mint_tokens_supply = {
+            let mut config = Config::load_mut(self.accounts.config)?;
 
-        let reserve_mint_x = vault_x.amount();
-        let reserve_mint_y = vault_y.amount();
+            // deposits are only allowed while the pool is fully active
+            config.check_state(&[AmmState::Initialized])?;
 
-        let lp_supply = vault_lp.amount();
+            // the reserves only count what came in through the pool's own instructions
+            let (reserve_mint_x, reserve_mint_y) = config.reserves();
 
-        let lp_mint_tokens_supply = if reserve_mint_x == 0 && reserve_mint_y == 0 {
-            let product = (self.instructions.mint_x as u128)
-                .checked_mul(self.instructions.mint_y as u128)
-                .ok_or_else(|| PinocchioError::MathOverflow)?;
+            // the price accumulators only ever see the reserves from before the deposit
+            config.update_price_accumulators(reserve_mint_x, reserve_mint_y, &clock);
 
-            if product == 0 {
-                return Err(PinocchioError::InvalidMintSupply.into());
+            if let Some(oracle) = self.accounts.oracle {
+                Oracle::load_mut(oracle)?.record(self.accounts.config.key(), &config)?;
             }
 
-            let sqrt_result = product.isqrt() as u64;
+            let lp_supply =
+                config.lp_supply(token_interface::mint(self.accounts.lp_mint)?.supply())?;
 
-            if sqrt_result < 1000 {
-                return Err(PinocchioError::InvalidMintSupply.into());
-            }
+            let quote = curve::deposit(
+                &config,
+                reserve_mint_x,
+                reserve_mint_y,
+                lp_supply,
+                received_x,
+                received_y,
+                clock.unix_timestamp,
+            )?;
+
+            config.deposit_reserves(received_x, received_y)?;
 
-            sqrt_result
-        } else {
-            if reserve_mint_x == 0 || reserve_mint_y == 0 || lp_supply == 0 {
-                return Err(PinocchioError::InvalidMintSupply.into());
-            };
-
-            let lp_from_x = (self.instructions.mint_x as u128)
-                .checked_mul(lp_supply as u128)
-                .ok_or_else(|| PinocchioError::MathOverflow)?
-                .checked_div(reserve_mint_x as u128)
-                .ok_or_else(|| PinocchioError::MathOverflow)? as u64;
-
-            let lp_from_y = (self.instructions.mint_y as u128)
-                .checked_mul(lp_supply as u128)
-                .ok_or_else(|| PinocchioError::MathOverflow)?
-                .checked_div(reserve_mint_y as u128)
-                .ok_or_else(|| PinocchioError::MathOverflow)? as u64;
-
-            cmp::min(lp_from_x, lp_from_y)
+            // an unbalanced deposit pays the protocol its share of the fee like a swap
+            config.accrue_deposit_fees(quote.fee_x, quote.fee_y)?;
+
+            let lp_amount = quote.lp_amount;
+
+            // part of the first deposit is locked forever, whoever deposits first can't later
+            // own the whole pool and inflate the value of a single lp token
+            match lp_supply {
+                0 => config.lock_minimum_liquidity(lp_amount)?,
+                _ => lp_amount,
+            }
         };
 
         if lp_mint_tokens_supply == 0 {
@@ -261,29 +247,40 @@ impl<'a> Deposit<'a> {
             return Err(PinocchioError::SlipageExceeded.into());
         }
 
-        Transfer {
+        TransferChecked {
             from: self.accounts.user_x_ata,
+            mint: self.accounts.mint_x,
             to: self.accounts.vault_x,
             amount: self.instructions.mint_x,
             authority: self.accounts.user,
+            decimals: token_interface::mint(self.accounts.mint_x)?.decimals(),
+            token_program: self.accounts.token_program_x.key(),
         }
         .invoke()?;
 
-        Transfer {
+        TransferChecked {
             from: self.accounts.user_y_ata,
+            mint: self.accounts.mint_y,
             to: self.accounts.vault_y,
             amount: self.instructions.mint_y,
             authority: self.accounts.user,
+            decimals: token_interface::mint(self.accounts.mint_y)?.decimals(),
+            token_program: self.accounts.token_program_y.key(),
         }
         .invoke()?;
 
+        let config = Config::load(self.accounts.config)?;
+        let config_seeds = config.signer_seeds();
+        let signer = [Signer::from(&config_seeds)];
+
         MintTo {
-            account: self.accounts.lp_mint,
+            account: self.accounts.vault_lp,
             mint: self.accounts.lp_mint,
             amount: lp_mint_tokens_supply,
             mint_authority: self.accounts.config,
+            token_program: self.accounts.token_program.key(),
         }
-        .invoke()?;
+        .invoke_signed(&signer)?;
         Ok(())
     }
 }
@@ -0,0 +1,278 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::Signer,
+    program_error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    curve,
+    error::PinocchioError,
+    state::{AmmState, Config, LimitOrder},
+    token_interface::{self, TransferChecked},
+};
+
+pub struct FillOrdersAccounts<'a> {
+    pub config: &'a AccountInfo,
+
+    pub mint_x: &'a AccountInfo,
+    pub mint_y: &'a AccountInfo,
+
+    pub vault_x: &'a AccountInfo,
+    pub vault_y: &'a AccountInfo,
+
+    pub token_program_x: &'a AccountInfo,
+    pub token_program_y: &'a AccountInfo,
+
+    // one `[order, order_vault, owner_ata_out]` triple per order to fill
+    pub orders: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for FillOrdersAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [config, mint_x, mint_y, vault_x, vault_y, token_program_x, token_program_y, orders @ ..] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if orders.is_empty() || orders.len() % 3 != 0 {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        let config_data = Config::load(config)?;
+        config_data.check_address(config.key())?;
+
+        if config_data.mint_x() != mint_x.key() || config_data.mint_y() != mint_y.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if config_data.mint_x_vault() != vault_x.key()
+            || config_data.mint_y_vault() != vault_y.key()
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        token_interface::check_token_program(token_program_x, mint_x)?;
+        token_interface::check_token_program(token_program_y, mint_y)?;
+
+        Ok(Self {
+            config,
+            mint_x,
+            mint_y,
+            vault_x,
+            vault_y,
+            token_program_x,
+            token_program_y,
+            orders,
+        })
+    }
+}
+
+// permissionless crank swapping the escrow of resting limit orders through the pool. an order
+// is filled in full once the pool pays its owner at least the limit price, the others are left
+// untouched so a single unfillable order doesn't fail the whole batch
+pub struct FillOrders<'a> {
+    pub accounts: FillOrdersAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for FillOrders<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let accounts = FillOrdersAccounts::try_from(accounts)?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> FillOrders<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &34;
+
+    pub fn process(&self) -> ProgramResult {
+        let clock = Clock::get()?;
+
+        let fee_x = token_interface::transfer_fee(self.accounts.mint_x, clock.epoch)?;
+        let fee_y = token_interface::transfer_fee(self.accounts.mint_y, clock.epoch)?;
+
+        let mut filled = 0;
+
+        for accounts in self.accounts.orders.chunks_exact(3) {
+            let [order, order_vault, owner_ata_out] = accounts else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+
+            let (is_x, amount, min_out) = {
+                let order_data = LimitOrder::load(order)?;
+                order_data.check_address(order.key())?;
+
+                if order_data.config() != self.accounts.config.key() {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+
+                let (mint_in, mint_out) = match order_data.is_x() {
+                    true => (self.accounts.mint_x, self.accounts.mint_y),
+                    false => (self.accounts.mint_y, self.accounts.mint_x),
+                };
+
+                {
+                    let vault = token_interface::token_account(order_vault)?;
+
+                    if vault.owner() != order.key() || vault.mint() != mint_in.key() {
+                        return Err(ProgramError::InvalidAccountData);
+                    }
+                }
+
+                // the proceeds can only go to the owner
+                let owner_ata = token_interface::token_account(owner_ata_out)?;
+
+                if owner_ata.owner() != order_data.owner() || owner_ata.mint() != mint_out.key() {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+
+                (order_data.is_x(), order_data.amount(), order_data.min_out())
+            };
+
+            // already filled, waiting for the owner to close it
+            if amount == 0 {
+                continue;
+            }
+
+            // no pool can pay a price that overflows a token amount, the owner can only cancel
+            let Some(min_out) = min_out else {
+                continue;
+            };
+
+            let (fee_in, fee_out) = match is_x {
+                true => (&fee_x, &fee_y),
+                false => (&fee_y, &fee_x),
+            };
+
+            let amount_received = fee_in
+                .amount_received(amount)
+                .ok_or(PinocchioError::MathOverflow)?;
+
+            let withdraw = {
+                let mut config = Config::load_mut(self.accounts.config)?;
+
+                config.check_state(&[AmmState::Initialized])?;
+
+                let (reserve_x, reserve_y) = config.reserves();
+
+                config.update_price_accumulators(reserve_x, reserve_y, &clock);
+
+                // quoting without a bound, the limit price is checked on what the owner receives
+                let Ok(swap_result) = curve::swap(
+                    &config,
+                    reserve_x,
+                    reserve_y,
+                    is_x,
+                    amount_received,
+                    0,
+                    clock.unix_timestamp,
+                ) else {
+                    continue;
+                };
+
+                let received = fee_out
+                    .amount_received(swap_result.withdraw)
+                    .ok_or(PinocchioError::MathOverflow)?;
+
+                if received < min_out {
+                    continue;
+                }
+
+                let protocol_fee = config.protocol_fee_amount(swap_result.fee)?;
+                config.accrue_protocol_fees(is_x, protocol_fee)?;
+
+                config.swap_reserves(
+                    is_x,
+                    swap_result.deposit - protocol_fee,
+                    swap_result.withdraw,
+                )?;
+
+                // fills move the price like any other swap
+                let new_reserves = config.reserves();
+                config.update_volatility(
+                    (reserve_x, reserve_y),
+                    new_reserves,
+                    clock.unix_timestamp,
+                );
+
+                swap_result.withdraw
+            };
+
+            let (mint_in, vault_in, token_program_in) = match is_x {
+                true => (
+                    self.accounts.mint_x,
+                    self.accounts.vault_x,
+                    self.accounts.token_program_x,
+                ),
+                false => (
+                    self.accounts.mint_y,
+                    self.accounts.vault_y,
+                    self.accounts.token_program_y,
+                ),
+            };
+
+            let (mint_out, vault_out, token_program_out) = match is_x {
+                true => (
+                    self.accounts.mint_y,
+                    self.accounts.vault_y,
+                    self.accounts.token_program_y,
+                ),
+                false => (
+                    self.accounts.mint_x,
+                    self.accounts.vault_x,
+                    self.accounts.token_program_x,
+                ),
+            };
+
+            {
+                let order_data = LimitOrder::load(order)?;
+                let order_seeds = order_data.signer_seeds();
+
+                // moving the escrow into the pool
+                TransferChecked {
+                    from: order_vault,
+                    mint: mint_in,
+                    to: vault_in,
+                    authority: order,
+                    amount,
+                    decimals: token_interface::mint(mint_in)?.decimals(),
+                    token_program: token_program_in.key(),
+                }
+                .invoke_signed(&[Signer::from(&order_seeds)])?;
+            }
+
+            {
+                let config = Config::load(self.accounts.config)?;
+                let config_seeds = config.signer_seeds();
+
+                // paying the owner out of the pool
+                TransferChecked {
+                    from: vault_out,
+                    mint: mint_out,
+                    to: owner_ata_out,
+                    authority: self.accounts.config,
+                    amount: withdraw,
+                    decimals: token_interface::mint(mint_out)?.decimals(),
+                    token_program: token_program_out.key(),
+                }
+                .invoke_signed(&[Signer::from(&config_seeds)])?;
+            }
+
+            LimitOrder::load_mut(order)?.fill();
+            filled += 1;
+        }
+
+        if filled == 0 {
+            return Err(PinocchioError::OrderNotFillable.into());
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,220 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::Signer,
+    program_error::ProgramError,
+    sysvars::{
+        clock::Clock,
+        instructions::{Instructions, INSTRUCTIONS_ID},
+        Sysvar,
+    },
+    ProgramResult,
+};
+
+use crate::{
+    error::PinocchioError,
+    instructions::{AccountCheck, SettleFlashSwap, SignerAccount},
+    state::Config,
+    token_interface::{self, TransferChecked},
+};
+
+pub struct FlashSwapAccounts<'a> {
+    pub user: &'a AccountInfo,
+
+    pub mint_x: &'a AccountInfo,
+    pub mint_y: &'a AccountInfo,
+
+    pub config: &'a AccountInfo,
+
+    pub vault_x: &'a AccountInfo,
+    pub vault_y: &'a AccountInfo,
+
+    // any token accounts of the right mints, the borrowed tokens are sent there
+    pub receiver_x: &'a AccountInfo,
+    pub receiver_y: &'a AccountInfo,
+
+    pub token_program_x: &'a AccountInfo,
+    pub token_program_y: &'a AccountInfo,
+
+    pub instruction_sysvar: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for FlashSwapAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [user, mint_x, mint_y, config, vault_x, vault_y, receiver_x, receiver_y, token_program_x, token_program_y, instruction_sysvar] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(user)?;
+
+        if instruction_sysvar.key() != &INSTRUCTIONS_ID {
+            return Err(ProgramError::UnsupportedSysvar);
+        }
+
+        // the mints and vaults must be the ones the pool was created with
+        let config_data = Config::load(config)?;
+        config_data.check_address(config.key())?;
+
+        if config_data.mint_x() != mint_x.key() || config_data.mint_y() != mint_y.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if config_data.mint_x_vault() != vault_x.key()
+            || config_data.mint_y_vault() != vault_y.key()
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        token_interface::check_token_program(token_program_x, mint_x)?;
+        token_interface::check_token_program(token_program_y, mint_y)?;
+
+        Ok(Self {
+            user,
+            mint_x,
+            mint_y,
+            config,
+            vault_x,
+            vault_y,
+            receiver_x,
+            receiver_y,
+            token_program_x,
+            token_program_y,
+            instruction_sysvar,
+        })
+    }
+}
+
+pub struct FlashSwapInstructions {
+    pub amount_x: u64,
+    pub amount_y: u64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for FlashSwapInstructions {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<u64>() * 2 {
+            return Err(ProgramError::InvalidInstructionData);
+        };
+
+        let amount_x = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let amount_y = u64::from_le_bytes(data[8..16].try_into().unwrap());
+
+        if amount_x == 0 && amount_y == 0 {
+            return Err(PinocchioError::InvalidAmount.into());
+        }
+
+        Ok(Self { amount_x, amount_y })
+    }
+}
+
+// sends tokens out of the pool before they are paid for, like a uniswap v2 flash swap. the pool
+// stays locked until a `SettleFlashSwap` later in the same transaction checks that what came
+// back in, less the swap fee, keeps the curve invariant
+pub struct FlashSwap<'a> {
+    pub accounts: FlashSwapAccounts<'a>,
+    pub instructions: FlashSwapInstructions,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for FlashSwap<'a> {
+    type Error = ProgramError;
+
+    fn try_from((accounts, data): (&'a [AccountInfo], &'a [u8])) -> Result<Self, Self::Error> {
+        let accounts = FlashSwapAccounts::try_from(accounts)?;
+        let instructions = FlashSwapInstructions::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instructions,
+        })
+    }
+}
+
+impl<'a> FlashSwap<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &23;
+
+    pub fn process(&self) -> ProgramResult {
+        self.check_settlement()?;
+
+        let clock = Clock::get()?;
+
+        {
+            let mut config = Config::load_mut(self.accounts.config)?;
+
+            let (reserve_x, reserve_y) = config.reserves();
+
+            // the pool can never be drained completely
+            if self.instructions.amount_x >= reserve_x || self.instructions.amount_y >= reserve_y {
+                return Err(PinocchioError::InvalidAmount.into());
+            }
+
+            // the price accumulators only ever see the reserves from before the swap
+            config.update_price_accumulators(reserve_x, reserve_y, &clock);
+
+            config.open_flash_swap(self.instructions.amount_x, self.instructions.amount_y)?;
+        }
+
+        let config = Config::load(self.accounts.config)?;
+        let config_seeds = config.signer_seeds();
+        let signer = [Signer::from(&config_seeds)];
+
+        if self.instructions.amount_x > 0 {
+            TransferChecked {
+                from: self.accounts.vault_x,
+                mint: self.accounts.mint_x,
+                to: self.accounts.receiver_x,
+                authority: self.accounts.config,
+                amount: self.instructions.amount_x,
+                decimals: token_interface::mint(self.accounts.mint_x)?.decimals(),
+                token_program: self.accounts.token_program_x.key(),
+            }
+            .invoke_signed(&signer)?;
+        }
+
+        if self.instructions.amount_y > 0 {
+            TransferChecked {
+                from: self.accounts.vault_y,
+                mint: self.accounts.mint_y,
+                to: self.accounts.receiver_y,
+                authority: self.accounts.config,
+                amount: self.instructions.amount_y,
+                decimals: token_interface::mint(self.accounts.mint_y)?.decimals(),
+                token_program: self.accounts.token_program_y.key(),
+            }
+            .invoke_signed(&signer)?;
+        }
+
+        Ok(())
+    }
+
+    // a settlement for this pool has to follow somewhere later in the transaction, if it fails
+    // the whole transaction including this instruction is rolled back
+    fn check_settlement(&self) -> ProgramResult {
+        let instruction_sysvar = unsafe {
+            Instructions::new_unchecked(self.accounts.instruction_sysvar.try_borrow_data()?)
+        };
+
+        let current = instruction_sysvar.load_current_index() as usize;
+        let num_instructions = instruction_sysvar.num_instructions() as usize;
+
+        for index in current + 1..num_instructions {
+            let instruction = instruction_sysvar.load_instruction_at(index)?;
+
+            if instruction.get_program_id() != &crate::ID
+                || instruction.get_instruction_data().first()
+                    != Some(SettleFlashSwap::DISCRIMINATOR)
+            {
+                continue;
+            }
+
+            if instruction.get_account_meta_at(0)?.key == *self.accounts.config.key() {
+                return Ok(());
+            }
+        }
+
+        Err(ProgramError::InvalidInstructionData)
+    }
+}
@@ -2,16 +2,25 @@ use pinocchio::{
     account_info::AccountInfo,
     instruction::{Seed, Signer},
     program_error::ProgramError,
-    pubkey::find_program_address,
+    pubkey::{find_program_address, Pubkey},
     sysvars::{rent::Rent, Sysvar},
+    ProgramResult,
 };
 use pinocchio_system::instructions::CreateAccount;
 use pinocchio_token::state::Mint;
 
+use crate::token_interface::{self, InitializeMint2};
+
 pub trait AccountCheck {
     fn check(account: &AccountInfo) -> Result<(), ProgramError>;
 }
 
+// optional accounts are left out by passing the program id in their place
+#[inline(always)]
+pub fn optional_account(account: Option<&AccountInfo>) -> Option<&AccountInfo> {
+    account.filter(|account| account.key() != &crate::ID)
+}
+
 pub struct SignerAccount;
 // account checks for the signer
 impl AccountCheck for SignerAccount {
@@ -24,28 +33,67 @@ impl AccountCheck for SignerAccount {
 }
 
 pub struct MintInterface;
-// mint accounts checks
+// mint accounts checks, legacy token and token-2022 mints are both accepted
 impl AccountCheck for MintInterface {
     fn check(account: &AccountInfo) -> Result<(), ProgramError> {
-        if account.data_len() != Mint::LEN {
-            return Err(ProgramError::InvalidAccountData);
+        token_interface::check_mint(account)
+    }
+}
+
+pub trait MintInit {
+    fn init<'a>(
+        account: &AccountInfo,
+        payer: &AccountInfo,
+        decimals: u8,
+        mint_authority: &Pubkey,
+        freeze_authority: Option<&Pubkey>,
+        seeds: &[Seed<'a>],
+        token_program: &Pubkey,
+    ) -> Result<(), ProgramError>;
+}
+
+impl MintInit for MintInterface {
+    fn init<'a>(
+        account: &AccountInfo,
+        payer: &AccountInfo,
+        decimals: u8,
+        mint_authority: &Pubkey,
+        freeze_authority: Option<&Pubkey>,
+        seeds: &[Seed<'a>],
+        token_program: &Pubkey,
+    ) -> Result<(), ProgramError> {
+        // get the lamports for the rent excempt
+        let rent_excempt = Rent::get()?.minimum_balance(Mint::LEN);
+
+        // the mint is a pda so the account creation is signed with its seeds
+        let signer = [Signer::from(seeds)];
+
+        CreateAccount {
+            from: payer,
+            to: account,
+            lamports: rent_excempt,
+            space: Mint::LEN as u64,
+            owner: token_program,
         }
+        .invoke_signed(&signer)?;
+
+        InitializeMint2 {
+            mint: account,
+            decimals,
+            mint_authority,
+            freeze_authority,
+            token_program,
+        }
+        .invoke()?;
         Ok(())
     }
 }
 
 pub struct TokenAccount;
-// token accounts checks
+// token accounts checks, extension sized token-2022 accounts included
 impl AccountCheck for TokenAccount {
     fn check(account: &AccountInfo) -> Result<(), ProgramError> {
-        if !account.is_owned_by(&pinocchio_token::ID) {
-            return Err(ProgramError::IllegalOwner.into());
-        }
-
-        if account.data_len() != pinocchio_token::state::TokenAccount::LEN {
-            return Err(ProgramError::InvalidAccountData);
-        }
-        Ok(())
+        token_interface::check_token_account(account)
     }
 }
 
@@ -79,13 +127,25 @@ impl ProgramAccountInit for ProgramAccount {
             to: account,
             lamports: rent_excempt,
             space: space as u64,
-            owner: account.owner(),
+            owner: &crate::ID,
         }
         .invoke_signed(&signer)?;
         Ok(())
     }
 }
 
+pub trait AccountClose {
+    fn close(account: &AccountInfo, destination: &AccountInfo) -> ProgramResult;
+}
+
+impl AccountClose for ProgramAccount {
+    fn close(account: &AccountInfo, destination: &AccountInfo) -> ProgramResult {
+        // the rent goes to the destination, the runtime reclaims the emptied account
+        *destination.try_borrow_mut_lamports()? += *account.try_borrow_lamports()?;
+        account.close()
+    }
+}
+
 // ata checks
 pub trait AssociatedTokenAccountCheck {
     fn check(
@@ -126,7 +186,12 @@ impl AssociatedTokenAccountCheck for AssociatedTokenAccount {
     ) -> Result<(), ProgramError> {
         TokenAccount::check(account)?;
 
-        let seeds: &[&[u8]] = &[authority.key(), &pinocchio_token::ID, mint.key()];
+        // the ata address depends on the token program owning the account
+        let seeds: &[&[u8]] = &[
+            authority.key(),
+            token_interface::token_program_of(account)?,
+            mint.key(),
+        ];
 
         if find_program_address(seeds, &pinocchio_associated_token_account::ID)
             .0
@@ -150,7 +215,7 @@ impl AssociatedTokenAccountInit for AssociatedTokenAccount {
         pinocchio_associated_token_account::instructions::Create {
             account: ata,
             funding_account: authority,
-            mint: mint,
+            mint,
             wallet: owner,
             system_program,
             token_program,
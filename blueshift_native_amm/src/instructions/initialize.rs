@@ -1,13 +1,21 @@
 use pinocchio::{
-    account_info::AccountInfo, instruction::Seed, program_error::ProgramError,
-    pubkey::find_program_address, ProgramResult,
+    account_info::AccountInfo,
+    instruction::Seed,
+    program_error::ProgramError,
+    pubkey::find_program_address,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
 };
 
 use crate::{
+    error::PinocchioError,
     instructions::{
-        AccountCheck, MintInterface, ProgramAccount, ProgramAccountInit, SignerAccount,
+        AccountCheck, AssociatedTokenAccount, AssociatedTokenAccountInit, MintInit, MintInterface,
+        ProgramAccount, ProgramAccountInit, SignerAccount,
     },
-    state::Config,
+    seeds,
+    state::{AmmState, Config, CurveType},
+    token_interface,
 };
 
 pub struct InitializeConfigAccounts<'a> {
@@ -22,6 +30,9 @@ pub struct InitializeConfigAccounts<'a> {
 
     pub lp_mint: &'a AccountInfo,
 
+    // each mint is passed with the token program owning it, the lp mint can use either one
+    pub token_program_x: &'a AccountInfo,
+    pub token_program_y: &'a AccountInfo,
     pub token_program: &'a AccountInfo,
     pub system_program: &'a AccountInfo,
     pub associated_token_program: &'a AccountInfo,
@@ -31,7 +42,7 @@ impl<'a> TryFrom<&'a [AccountInfo]> for InitializeConfigAccounts<'a> {
     type Error = ProgramError;
 
     fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
-        let [authority, config, mint_x, mint_y, vault_x, vault_y, lp_mint, token_program, system_program, associated_token_program] =
+        let [authority, config, mint_x, mint_y, vault_x, vault_y, lp_mint, token_program_x, token_program_y, token_program, system_program, associated_token_program] =
             accounts
         else {
             return Err(ProgramError::InvalidAccountData);
@@ -45,6 +56,17 @@ impl<'a> TryFrom<&'a [AccountInfo]> for InitializeConfigAccounts<'a> {
             return Err(ProgramError::InvalidAccountData);
         }
 
+        token_interface::check_token_program(token_program_x, mint_x)?;
+        token_interface::check_token_program(token_program_y, mint_y)?;
+
+        // token-2022 mints can only carry extensions the pool knows how to handle
+        token_interface::check_extensions(mint_x)?;
+        token_interface::check_extensions(mint_y)?;
+
+        if !token_interface::is_token_program(token_program.key()) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
         Ok(Self {
             authority,
             config,
@@ -53,6 +75,8 @@ impl<'a> TryFrom<&'a [AccountInfo]> for InitializeConfigAccounts<'a> {
             vault_x,
             vault_y,
             lp_mint,
+            token_program_x,
+            token_program_y,
             token_program,
             system_program,
             associated_token_program,
@@ -60,24 +84,147 @@ impl<'a> TryFrom<&'a [AccountInfo]> for InitializeConfigAccounts<'a> {
     }
 }
 
+impl InitializeConfigAccounts<'_> {
+    // creates the config account at the address signed for by `config_seeds`, the lp mint and
+    // the vaults, returns the lp mint bump. the config data is left for the caller to set
+    pub fn init_pool(&self, config_seeds: &[Seed]) -> Result<u8, ProgramError> {
+        let (lp_pda, lp_bump) =
+            find_program_address(&seeds::lp_mint(self.config.key()), &crate::ID);
+
+        if &lp_pda != self.lp_mint.key() {
+            return Err(ProgramError::InvalidAccountData);
+        };
+
+        // the vaults have to be the config's associated token accounts
+        let (vault_x_pda, _) = find_program_address(
+            &seeds::vault(
+                self.config.key(),
+                self.token_program_x.key(),
+                self.mint_x.key(),
+            ),
+            &pinocchio_associated_token_account::ID,
+        );
+        let (vault_y_pda, _) = find_program_address(
+            &seeds::vault(
+                self.config.key(),
+                self.token_program_y.key(),
+                self.mint_y.key(),
+            ),
+            &pinocchio_associated_token_account::ID,
+        );
+
+        if &vault_x_pda != self.vault_x.key() || &vault_y_pda != self.vault_y.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // create the config account
+        ProgramAccount::init::<Config>(self.authority, self.config, config_seeds, Config::LEN)?;
+
+        let lp_bump_binding = [lp_bump];
+        let lp_mint_seeds = seeds::lp_mint_signer(self.config.key(), &lp_bump_binding);
+
+        // create the lp mint, only the config pda can mint new lp tokens
+        MintInterface::init(
+            self.lp_mint,
+            self.authority,
+            InitializeConfig::LP_DECIMALS,
+            self.config.key(),
+            None,
+            &lp_mint_seeds,
+            self.token_program.key(),
+        )?;
+
+        // create the config owned vaults which hold the pool reserves
+        AssociatedTokenAccount::init(
+            self.vault_x,
+            self.mint_x,
+            self.authority,
+            self.config,
+            self.system_program,
+            self.token_program_x,
+        )?;
+
+        AssociatedTokenAccount::init(
+            self.vault_y,
+            self.mint_y,
+            self.authority,
+            self.config,
+            self.system_program,
+            self.token_program_y,
+        )?;
+
+        Ok(lp_bump)
+    }
+}
+
 pub struct InitializeConfigInstruction {
+    pub seed: u64,
     pub fee: u16,
+    pub protocol_fee: u16,
+    pub curve_type: CurveType,
+    pub amp: u64,
+    pub weight_x: u16,
+    pub weight_y: u16,
 }
 
 impl<'a> TryFrom<&'a [u8]> for InitializeConfigInstruction {
     type Error = ProgramError;
 
     fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
-        if data.len() < 2 {
+        if data.len() != size_of::<u64>() * 2 + size_of::<u16>() * 3 + size_of::<u8>() {
             return Err(ProgramError::InvalidAccountData);
         };
 
-        let fee = u16::from_le_bytes([data[0], data[1]]);
+        let seed = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let fee = u16::from_le_bytes([data[8], data[9]]);
+        let protocol_fee = u16::from_le_bytes([data[10], data[11]]);
+        let curve_type = CurveType::try_from(data[12])?;
+        let amp = u64::from_le_bytes(data[13..21].try_into().unwrap());
+        let weight_x = u16::from_le_bytes([data[21], data[22]]);
 
-        if fee > 1000 {
+        if fee > Config::MAX_FEE || protocol_fee > Config::MAX_PROTOCOL_FEE {
             return Err(ProgramError::InvalidAccountData);
         };
-        Ok(Self { fee })
+
+        // only stable pools are amplified
+        let valid_amp = match curve_type {
+            CurveType::Stable => (Config::MIN_AMP..=Config::MAX_AMP).contains(&amp),
+            _ => amp == 0,
+        };
+
+        if !valid_amp {
+            return Err(PinocchioError::InvalidAmp.into());
+        }
+
+        // and only weighted pools have weights, y gets whatever x leaves
+        let weight_y = match curve_type {
+            CurveType::Weighted => {
+                if !(Config::MIN_WEIGHT..=Config::TOTAL_WEIGHT - Config::MIN_WEIGHT)
+                    .contains(&weight_x)
+                {
+                    return Err(PinocchioError::InvalidWeights.into());
+                }
+
+                Config::TOTAL_WEIGHT - weight_x
+            }
+            _ => {
+                if weight_x != 0 {
+                    return Err(PinocchioError::InvalidWeights.into());
+                }
+
+                0
+            }
+        };
+
+        Ok(Self {
+            seed,
+            fee,
+            protocol_fee,
+            curve_type,
+            amp,
+            weight_x,
+            weight_y,
+        })
     }
 }
 
@@ -103,50 +250,43 @@ impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for InitializeConfig<'a> {
 impl<'a> InitializeConfig<'a> {
     pub const DISCRIMINATOR: &'a u8 = &0;
 
+    pub const LP_DECIMALS: u8 = 6;
+
     pub fn process(&self) -> ProgramResult {
         // signers check
         SignerAccount::check(self.accounts.authority)?;
 
-        if self.accounts.vault_x.key() != self.accounts.token_program.key()
-            || self.accounts.vault_y.key() != self.accounts.token_program.key()
-        {
-            return Err(ProgramError::InvalidAccountData);
-        }
-
-        let seeds_slice = &[b"config", self.accounts.authority.key().as_ref()];
+        let seed_binding = self.instruction.seed.to_le_bytes();
 
-        let (config_pda, bump) = find_program_address(seeds_slice, &crate::ID);
-
-        let lp_seed = [
-            b"lp_mint",
-            self.accounts.mint_x.key().as_ref(),
-            self.accounts.mint_y.key().as_ref(),
-        ];
-        let (_lp_pda, lp_bump) = find_program_address(&lp_seed, &crate::ID);
+        let (config_pda, bump) = find_program_address(
+            &seeds::config(
+                &seed_binding,
+                self.accounts.mint_x.key(),
+                self.accounts.mint_y.key(),
+            ),
+            &crate::ID,
+        );
 
         if &config_pda != self.accounts.config.key() {
             return Err(ProgramError::InvalidAccountData);
         };
 
-        let config_seeds = [
-            Seed::from(b"config"),
-            Seed::from(self.accounts.mint_x.key()),
-            Seed::from(self.accounts.mint_y.key()),
-        ];
+        let bump_binding = [bump];
+        let config_seeds = seeds::config_signer(
+            &seed_binding,
+            self.accounts.mint_x.key(),
+            self.accounts.mint_y.key(),
+            &bump_binding,
+        );
 
-        // create the config account
-        ProgramAccount::init::<Config>(
-            self.accounts.authority,
-            self.accounts.config,
-            &config_seeds,
-            Config::LEN,
-        )?;
+        let lp_bump = self.accounts.init_pool(&config_seeds)?;
 
         // get the config account mutable data
         let mut config_data = Config::load_mut(self.accounts.config)?;
 
         // set the config account data
         config_data.set_inner(
+            self.instruction.seed,
             *self.accounts.authority.key(),
             *self.accounts.mint_x.key(),
             *self.accounts.mint_y.key(),
@@ -154,10 +294,19 @@ impl<'a> InitializeConfig<'a> {
             *self.accounts.vault_y.key(),
             *self.accounts.lp_mint.key(),
             self.instruction.fee,
+            self.instruction.protocol_fee,
             bump,
             lp_bump,
+            AmmState::Initialized as u8,
+            self.instruction.curve_type,
+            self.instruction.amp,
+            self.instruction.weight_x,
+            self.instruction.weight_y,
         )?;
 
+        // the accumulators start counting from pool creation
+        config_data.update_price_accumulators(0, 0, &Clock::get()?);
+
         Ok(())
     }
 }
@@ -0,0 +1,238 @@
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::find_program_address,
+    ProgramResult,
+};
+
+use crate::{
+    curve::concentrated::{tick_at_sqrt_price, MAX_SQRT_PRICE, MIN_SQRT_PRICE},
+    error::PinocchioError,
+    instructions::{
+        AccountCheck, AssociatedTokenAccount, AssociatedTokenAccountInit, MintInterface,
+        ProgramAccount, ProgramAccountInit, SignerAccount,
+    },
+    seeds,
+    state::{ConcentratedPool, Config},
+    token_interface,
+};
+
+pub struct InitializeConcentratedPoolAccounts<'a> {
+    pub authority: &'a AccountInfo,
+    pub pool: &'a AccountInfo,
+
+    pub mint_x: &'a AccountInfo,
+    pub mint_y: &'a AccountInfo,
+
+    pub vault_x: &'a AccountInfo,
+    pub vault_y: &'a AccountInfo,
+
+    pub token_program_x: &'a AccountInfo,
+    pub token_program_y: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    pub associated_token_program: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for InitializeConcentratedPoolAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [authority, pool, mint_x, mint_y, vault_x, vault_y, token_program_x, token_program_y, system_program, associated_token_program] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(authority)?;
+        MintInterface::check(mint_x)?;
+        MintInterface::check(mint_y)?;
+
+        if mint_x.key() == mint_y.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        token_interface::check_token_program(token_program_x, mint_x)?;
+        token_interface::check_token_program(token_program_y, mint_y)?;
+
+        token_interface::check_extensions(mint_x)?;
+        token_interface::check_extensions(mint_y)?;
+
+        // the tick math assumes the vaults receive exactly what the swap step consumed
+        if token_interface::has_transfer_fee(mint_x)? || token_interface::has_transfer_fee(mint_y)?
+        {
+            return Err(PinocchioError::UnsupportedExtension.into());
+        }
+
+        Ok(Self {
+            authority,
+            pool,
+            mint_x,
+            mint_y,
+            vault_x,
+            vault_y,
+            token_program_x,
+            token_program_y,
+            system_program,
+            associated_token_program,
+        })
+    }
+}
+
+pub struct InitializeConcentratedPoolInstructions {
+    pub seed: u64,
+    pub fee: u16,
+    pub tick_spacing: u16,
+    pub sqrt_price: u128,
+    pub protocol_fee: u16,
+}
+
+impl<'a> TryFrom<&'a [u8]> for InitializeConcentratedPoolInstructions {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<u64>() + size_of::<u16>() * 3 + size_of::<u128>() {
+            return Err(ProgramError::InvalidInstructionData);
+        };
+
+        let seed = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let fee = u16::from_le_bytes([data[8], data[9]]);
+        let tick_spacing = u16::from_le_bytes([data[10], data[11]]);
+        let sqrt_price = u128::from_le_bytes(data[12..28].try_into().unwrap());
+        let protocol_fee = u16::from_le_bytes([data[28], data[29]]);
+
+        if fee > Config::MAX_FEE || protocol_fee > Config::MAX_PROTOCOL_FEE {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        if tick_spacing == 0 || tick_spacing > ConcentratedPool::MAX_TICK_SPACING {
+            return Err(PinocchioError::InvalidTickRange.into());
+        }
+
+        // the price has to sit strictly below the top so there is always a tick above it
+        if !(MIN_SQRT_PRICE..MAX_SQRT_PRICE).contains(&sqrt_price) {
+            return Err(PinocchioError::InvalidSqrtPrice.into());
+        }
+
+        Ok(Self {
+            seed,
+            fee,
+            tick_spacing,
+            sqrt_price,
+            protocol_fee,
+        })
+    }
+}
+
+// creates a concentrated liquidity pool at a starting price, liquidity comes in via positions.
+// the creator becomes the pool authority
+pub struct InitializeConcentratedPool<'a> {
+    pub accounts: InitializeConcentratedPoolAccounts<'a>,
+    pub instructions: InitializeConcentratedPoolInstructions,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for InitializeConcentratedPool<'a> {
+    type Error = ProgramError;
+
+    fn try_from((accounts, data): (&'a [AccountInfo], &'a [u8])) -> Result<Self, Self::Error> {
+        let accounts = InitializeConcentratedPoolAccounts::try_from(accounts)?;
+        let instructions = InitializeConcentratedPoolInstructions::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instructions,
+        })
+    }
+}
+
+impl<'a> InitializeConcentratedPool<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &15;
+
+    pub fn process(&self) -> ProgramResult {
+        let seed_binding = self.instructions.seed.to_le_bytes();
+
+        let (pool_pda, bump) = find_program_address(
+            &seeds::concentrated_pool(
+                &seed_binding,
+                self.accounts.mint_x.key(),
+                self.accounts.mint_y.key(),
+            ),
+            &crate::ID,
+        );
+
+        if &pool_pda != self.accounts.pool.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // the vaults have to be the pool's associated token accounts
+        let (vault_x_pda, _) = find_program_address(
+            &seeds::vault(
+                self.accounts.pool.key(),
+                self.accounts.token_program_x.key(),
+                self.accounts.mint_x.key(),
+            ),
+            &pinocchio_associated_token_account::ID,
+        );
+        let (vault_y_pda, _) = find_program_address(
+            &seeds::vault(
+                self.accounts.pool.key(),
+                self.accounts.token_program_y.key(),
+                self.accounts.mint_y.key(),
+            ),
+            &pinocchio_associated_token_account::ID,
+        );
+
+        if &vault_x_pda != self.accounts.vault_x.key()
+            || &vault_y_pda != self.accounts.vault_y.key()
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let bump_binding = [bump];
+        let pool_seeds = seeds::concentrated_pool_signer(
+            &seed_binding,
+            self.accounts.mint_x.key(),
+            self.accounts.mint_y.key(),
+            &bump_binding,
+        );
+
+        ProgramAccount::init::<ConcentratedPool>(
+            self.accounts.authority,
+            self.accounts.pool,
+            &pool_seeds,
+            ConcentratedPool::LEN,
+        )?;
+
+        AssociatedTokenAccount::init(
+            self.accounts.vault_x,
+            self.accounts.mint_x,
+            self.accounts.authority,
+            self.accounts.pool,
+            self.accounts.system_program,
+            self.accounts.token_program_x,
+        )?;
+
+        AssociatedTokenAccount::init(
+            self.accounts.vault_y,
+            self.accounts.mint_y,
+            self.accounts.authority,
+            self.accounts.pool,
+            self.accounts.system_program,
+            self.accounts.token_program_y,
+        )?;
+
+        ConcentratedPool::load_mut(self.accounts.pool)?.set_inner(
+            self.instructions.seed,
+            *self.accounts.authority.key(),
+            *self.accounts.mint_x.key(),
+            *self.accounts.mint_y.key(),
+            *self.accounts.vault_x.key(),
+            *self.accounts.vault_y.key(),
+            self.instructions.fee,
+            self.instructions.tick_spacing,
+            bump,
+            self.instructions.sqrt_price,
+            tick_at_sqrt_price(self.instructions.sqrt_price)?,
+            self.instructions.protocol_fee,
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,140 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::{find_program_address, Pubkey},
+    ProgramResult,
+};
+
+use crate::{
+    error::PinocchioError,
+    instructions::{AccountCheck, ProgramAccount, ProgramAccountInit, SignerAccount},
+    seeds,
+    state::{Config, Factory},
+};
+
+// BPFLoaderUpgradeab1e11111111111111111111111
+const BPF_LOADER_UPGRADEABLE_ID: Pubkey = [
+    0x02, 0xa8, 0xf6, 0x91, 0x4e, 0x88, 0xa1, 0xb0, 0xe2, 0x10, 0x15, 0x3e, 0xf7, 0x63, 0xae, 0x2b,
+    0x00, 0xc2, 0xb9, 0x3d, 0x16, 0xc1, 0x24, 0xd2, 0xc0, 0x53, 0x7a, 0x10, 0x04, 0x80, 0x00, 0x00,
+];
+
+pub struct InitializeFactoryAccounts<'a> {
+    pub authority: &'a AccountInfo,
+    pub factory: &'a AccountInfo,
+    // the program's own program data account, it holds the upgrade authority
+    pub program_data: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for InitializeFactoryAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [authority, factory, program_data, system_program] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(authority)?;
+
+        // the factory authority controls every canonical pool, only the upgrade authority of the
+        // program can claim it. otherwise anyone could front-run the deployment
+        let (program_data_pda, _) = find_program_address(&[&crate::ID], &BPF_LOADER_UPGRADEABLE_ID);
+
+        if &program_data_pda != program_data.key()
+            || !program_data.is_owned_by(&BPF_LOADER_UPGRADEABLE_ID)
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // program data layout: u32 account type (3), u64 slot, option<pubkey> upgrade authority
+        let data = program_data.try_borrow_data()?;
+
+        if data.len() < 45 || data[0..4] != 3u32.to_le_bytes() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // an immutable program has no upgrade authority, its factory can't be created anymore
+        if data[12] != 1 || data[13..45] != *authority.key() {
+            return Err(PinocchioError::InvalidAuthority.into());
+        }
+
+        Ok(Self {
+            authority,
+            factory,
+            program_data,
+            system_program,
+        })
+    }
+}
+
+pub struct InitializeFactoryInstructions {
+    pub protocol_fee: u16,
+}
+
+impl<'a> TryFrom<&'a [u8]> for InitializeFactoryInstructions {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<u16>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let protocol_fee = u16::from_le_bytes([data[0], data[1]]);
+
+        if protocol_fee > Config::MAX_PROTOCOL_FEE {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self { protocol_fee })
+    }
+}
+
+// creates the single factory of the program, only the program's upgrade authority can create it
+// and it becomes the factory authority. it starts without any fee tiers, they are added with
+// `AddFeeTier`
+pub struct InitializeFactory<'a> {
+    pub accounts: InitializeFactoryAccounts<'a>,
+    pub instructions: InitializeFactoryInstructions,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for InitializeFactory<'a> {
+    type Error = ProgramError;
+
+    fn try_from((accounts, data): (&'a [AccountInfo], &'a [u8])) -> Result<Self, Self::Error> {
+        let accounts = InitializeFactoryAccounts::try_from(accounts)?;
+        let instructions = InitializeFactoryInstructions::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instructions,
+        })
+    }
+}
+
+impl<'a> InitializeFactory<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &29;
+
+    pub fn process(&self) -> ProgramResult {
+        let (factory_pda, bump) = find_program_address(&seeds::factory(), &crate::ID);
+
+        if &factory_pda != self.accounts.factory.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let bump_binding = [bump];
+        let factory_seeds = seeds::factory_signer(&bump_binding);
+
+        ProgramAccount::init::<Factory>(
+            self.accounts.authority,
+            self.accounts.factory,
+            &factory_seeds,
+            Factory::LEN,
+        )?;
+
+        Factory::load_mut(self.accounts.factory)?.set_inner(
+            *self.accounts.authority.key(),
+            self.instructions.protocol_fee,
+            bump,
+        )
+    }
+}
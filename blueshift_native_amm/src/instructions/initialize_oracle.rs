@@ -0,0 +1,86 @@
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::find_program_address,
+    ProgramResult,
+};
+
+use crate::{
+    instructions::{AccountCheck, ProgramAccount, ProgramAccountInit, SignerAccount},
+    seeds,
+    state::{Config, Oracle},
+};
+
+pub struct InitializeOracleAccounts<'a> {
+    pub payer: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+    pub oracle: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for InitializeOracleAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [payer, config, oracle, system_program] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(payer)?;
+
+        // the oracle can only be attached to an existing pool
+        Config::load(config)?.check_address(config.key())?;
+
+        Ok(Self {
+            payer,
+            config,
+            oracle,
+            system_program,
+        })
+    }
+}
+
+// anyone can pay for the observation account of a pool
+pub struct InitializeOracle<'a> {
+    pub accounts: InitializeOracleAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for InitializeOracle<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let accounts = InitializeOracleAccounts::try_from(accounts)?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> InitializeOracle<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &11;
+
+    pub fn process(&self) -> ProgramResult {
+        let (oracle_pda, bump) =
+            find_program_address(&seeds::oracle(self.accounts.config.key()), &crate::ID);
+
+        if &oracle_pda != self.accounts.oracle.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let bump_binding = [bump];
+        let oracle_seeds = seeds::oracle_signer(self.accounts.config.key(), &bump_binding);
+
+        ProgramAccount::init::<Oracle>(
+            self.accounts.payer,
+            self.accounts.oracle,
+            &oracle_seeds,
+            Oracle::LEN,
+        )?;
+
+        let mut oracle = Oracle::load_mut(self.accounts.oracle)?;
+        oracle.set_inner(*self.accounts.config.key(), bump);
+
+        // seed the buffer with the accumulators as they are now
+        let config = Config::load(self.accounts.config)?;
+        oracle.record(self.accounts.config.key(), &config)?;
+
+        Ok(())
+    }
+}
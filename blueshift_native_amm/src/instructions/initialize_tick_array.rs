@@ -0,0 +1,122 @@
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::find_program_address,
+    ProgramResult,
+};
+
+use crate::{
+    curve::concentrated::{MAX_TICK, MIN_TICK},
+    error::PinocchioError,
+    instructions::{AccountCheck, ProgramAccount, ProgramAccountInit, SignerAccount},
+    seeds,
+    state::{ConcentratedPool, TickArray},
+};
+
+pub struct InitializeTickArrayAccounts<'a> {
+    pub payer: &'a AccountInfo,
+    pub pool: &'a AccountInfo,
+    pub tick_array: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for InitializeTickArrayAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [payer, pool, tick_array, system_program] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(payer)?;
+
+        ConcentratedPool::load(pool)?.check_address(pool.key())?;
+
+        Ok(Self {
+            payer,
+            pool,
+            tick_array,
+            system_program,
+        })
+    }
+}
+
+pub struct InitializeTickArrayInstructions {
+    pub start_tick_index: i32,
+}
+
+impl<'a> TryFrom<&'a [u8]> for InitializeTickArrayInstructions {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<i32>() {
+            return Err(ProgramError::InvalidInstructionData);
+        };
+
+        let start_tick_index = i32::from_le_bytes(data[0..4].try_into().unwrap());
+
+        Ok(Self { start_tick_index })
+    }
+}
+
+// anyone can pay for the tick arrays a position or a swap needs
+pub struct InitializeTickArray<'a> {
+    pub accounts: InitializeTickArrayAccounts<'a>,
+    pub instructions: InitializeTickArrayInstructions,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for InitializeTickArray<'a> {
+    type Error = ProgramError;
+
+    fn try_from((accounts, data): (&'a [AccountInfo], &'a [u8])) -> Result<Self, Self::Error> {
+        let accounts = InitializeTickArrayAccounts::try_from(accounts)?;
+        let instructions = InitializeTickArrayInstructions::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instructions,
+        })
+    }
+}
+
+impl<'a> InitializeTickArray<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &16;
+
+    pub fn process(&self) -> ProgramResult {
+        let tick_spacing = ConcentratedPool::load(self.accounts.pool)?.tick_spacing();
+        let start_tick_index = self.instructions.start_tick_index;
+
+        // arrays tile the tick range, each one has to start on its own boundary
+        if start_tick_index != TickArray::start_index_for(start_tick_index, tick_spacing)
+            || start_tick_index > MAX_TICK
+            || start_tick_index + TickArray::span(tick_spacing) <= MIN_TICK
+        {
+            return Err(PinocchioError::InvalidTickArray.into());
+        }
+
+        let start_binding = start_tick_index.to_le_bytes();
+
+        let (tick_array_pda, bump) = find_program_address(
+            &seeds::tick_array(self.accounts.pool.key(), &start_binding),
+            &crate::ID,
+        );
+
+        if &tick_array_pda != self.accounts.tick_array.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let bump_binding = [bump];
+        let tick_array_seeds =
+            seeds::tick_array_signer(self.accounts.pool.key(), &start_binding, &bump_binding);
+
+        ProgramAccount::init::<TickArray>(
+            self.accounts.payer,
+            self.accounts.tick_array,
+            &tick_array_seeds,
+            TickArray::LEN,
+        )?;
+
+        TickArray::load_mut(self.accounts.tick_array)?
+            .set_inner(*self.accounts.pool.key(), start_tick_index);
+
+        Ok(())
+    }
+}
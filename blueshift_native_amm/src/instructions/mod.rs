@@ -1,11 +1,77 @@
+pub mod accept_authority;
+pub mod add_fee_tier;
+pub mod cancel_order;
+pub mod collect_concentrated_protocol_fees;
+pub mod collect_protocol_fees;
+pub mod concentrated_swap;
+pub mod create_pool;
+pub mod decrease_liquidity;
 pub mod deposit;
+pub mod fill_orders;
+pub mod flash_swap;
 pub mod helper;
+pub mod increase_liquidity;
 pub mod initialize;
+pub mod initialize_concentrated_pool;
+pub mod initialize_factory;
+pub mod initialize_oracle;
+pub mod initialize_tick_array;
+pub mod observe;
+pub mod open_position;
+pub mod place_order;
+pub mod ramp_amp;
+pub mod renounce_authority;
+pub mod route_swap;
+pub mod set_authority;
+pub mod settle_flash_swap;
+pub mod skim;
+pub mod stop_ramp_amp;
 pub mod swap;
+pub mod swap_exact_out;
+pub mod sync_reserves;
+pub mod update_concentrated_state;
+pub mod update_dynamic_fee;
+pub mod update_fee;
+pub mod update_protocol_fee;
+pub mod update_state;
 pub mod withdraw;
+pub mod zap_deposit;
 
+pub use accept_authority::*;
+pub use add_fee_tier::*;
+pub use cancel_order::*;
+pub use collect_concentrated_protocol_fees::*;
+pub use collect_protocol_fees::*;
+pub use concentrated_swap::*;
+pub use create_pool::*;
+pub use decrease_liquidity::*;
 pub use deposit::*;
+pub use fill_orders::*;
+pub use flash_swap::*;
 pub use helper::*;
+pub use increase_liquidity::*;
 pub use initialize::*;
+pub use initialize_concentrated_pool::*;
+pub use initialize_factory::*;
+pub use initialize_oracle::*;
+pub use initialize_tick_array::*;
+pub use observe::*;
+pub use open_position::*;
+pub use place_order::*;
+pub use ramp_amp::*;
+pub use renounce_authority::*;
+pub use route_swap::*;
+pub use set_authority::*;
+pub use settle_flash_swap::*;
+pub use skim::*;
+pub use stop_ramp_amp::*;
 pub use swap::*;
+pub use swap_exact_out::*;
+pub use sync_reserves::*;
+pub use update_concentrated_state::*;
+pub use update_dynamic_fee::*;
+pub use update_fee::*;
+pub use update_protocol_fee::*;
+pub use update_state::*;
 pub use withdraw::*;
+pub use zap_deposit::*;
@@ -0,0 +1,118 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    cpi::set_return_data,
+    program_error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    error::PinocchioError,
+    state::{Config, Observation, Oracle},
+};
+
+pub struct ObserveAccounts<'a> {
+    pub config: &'a AccountInfo,
+    pub oracle: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for ObserveAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [config, oracle] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Config::load(config)?.check_address(config.key())?;
+
+        if Oracle::load(oracle)?.config() != config.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(Self { config, oracle })
+    }
+}
+
+pub struct ObserveInstructions {
+    pub seconds_ago: u32,
+}
+
+impl<'a> TryFrom<&'a [u8]> for ObserveInstructions {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<u32>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let seconds_ago = u32::from_le_bytes(data.try_into().unwrap());
+
+        if seconds_ago == 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self { seconds_ago })
+    }
+}
+
+// read only, returns the q64.64 twap of x and y over the last `seconds_ago` seconds
+// as 32 bytes of return data
+pub struct Observe<'a> {
+    pub accounts: ObserveAccounts<'a>,
+    pub instructions: ObserveInstructions,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for Observe<'a> {
+    type Error = ProgramError;
+
+    fn try_from((accounts, data): (&'a [AccountInfo], &'a [u8])) -> Result<Self, Self::Error> {
+        let accounts = ObserveAccounts::try_from(accounts)?;
+        let instructions = ObserveInstructions::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instructions,
+        })
+    }
+}
+
+impl<'a> Observe<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &12;
+
+    pub fn process(&self) -> ProgramResult {
+        let config = Config::load(self.accounts.config)?;
+        let oracle = Oracle::load(self.accounts.oracle)?;
+        let clock = Clock::get()?;
+
+        let (reserve_x, reserve_y) = config.reserves();
+
+        // the accumulators carried forward to now, as if an instruction had just touched the pool
+        let (price_x_cumulative, price_y_cumulative) =
+            config.cumulative_prices_at(reserve_x, reserve_y, clock.unix_timestamp);
+        let current = Observation::new(
+            clock.unix_timestamp,
+            clock.slot,
+            price_x_cumulative,
+            price_y_cumulative,
+        );
+
+        let target = clock
+            .unix_timestamp
+            .checked_sub(self.instructions.seconds_ago as i64)
+            .ok_or(PinocchioError::MathOverflow)?;
+
+        let (twap_x, twap_y) = oracle
+            .observation_before(target)
+            .and_then(|older| older.twap(&current))
+            .ok_or(PinocchioError::InsufficientObservations)?;
+
+        let mut return_data = [0u8; 32];
+        return_data[..16].copy_from_slice(&twap_x.to_le_bytes());
+        return_data[16..].copy_from_slice(&twap_y.to_le_bytes());
+
+        set_return_data(&return_data);
+
+        Ok(())
+    }
+}
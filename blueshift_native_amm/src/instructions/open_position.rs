@@ -0,0 +1,136 @@
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::find_program_address,
+    ProgramResult,
+};
+
+use crate::{
+    curve::concentrated::check_tick_range,
+    instructions::{AccountCheck, ProgramAccount, ProgramAccountInit, SignerAccount},
+    seeds,
+    state::{ConcentratedPool, Position},
+};
+
+pub struct OpenPositionAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub pool: &'a AccountInfo,
+    pub position: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for OpenPositionAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [owner, pool, position, system_program] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(owner)?;
+
+        ConcentratedPool::load(pool)?.check_address(pool.key())?;
+
+        Ok(Self {
+            owner,
+            pool,
+            position,
+            system_program,
+        })
+    }
+}
+
+pub struct OpenPositionInstructions {
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+}
+
+impl<'a> TryFrom<&'a [u8]> for OpenPositionInstructions {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<i32>() * 2 {
+            return Err(ProgramError::InvalidInstructionData);
+        };
+
+        let tick_lower = i32::from_le_bytes(data[0..4].try_into().unwrap());
+        let tick_upper = i32::from_le_bytes(data[4..8].try_into().unwrap());
+
+        Ok(Self {
+            tick_lower,
+            tick_upper,
+        })
+    }
+}
+
+// opens an empty position between two ticks, liquidity is added with increase liquidity
+pub struct OpenPosition<'a> {
+    pub accounts: OpenPositionAccounts<'a>,
+    pub instructions: OpenPositionInstructions,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for OpenPosition<'a> {
+    type Error = ProgramError;
+
+    fn try_from((accounts, data): (&'a [AccountInfo], &'a [u8])) -> Result<Self, Self::Error> {
+        let accounts = OpenPositionAccounts::try_from(accounts)?;
+        let instructions = OpenPositionInstructions::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instructions,
+        })
+    }
+}
+
+impl<'a> OpenPosition<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &17;
+
+    pub fn process(&self) -> ProgramResult {
+        check_tick_range(
+            self.instructions.tick_lower,
+            self.instructions.tick_upper,
+            ConcentratedPool::load(self.accounts.pool)?.tick_spacing(),
+        )?;
+
+        let lower_binding = self.instructions.tick_lower.to_le_bytes();
+        let upper_binding = self.instructions.tick_upper.to_le_bytes();
+
+        let (position_pda, bump) = find_program_address(
+            &seeds::position(
+                self.accounts.pool.key(),
+                self.accounts.owner.key(),
+                &lower_binding,
+                &upper_binding,
+            ),
+            &crate::ID,
+        );
+
+        if &position_pda != self.accounts.position.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let bump_binding = [bump];
+        let position_seeds = seeds::position_signer(
+            self.accounts.pool.key(),
+            self.accounts.owner.key(),
+            &lower_binding,
+            &upper_binding,
+            &bump_binding,
+        );
+
+        ProgramAccount::init::<Position>(
+            self.accounts.owner,
+            self.accounts.position,
+            &position_seeds,
+            Position::LEN,
+        )?;
+
+        Position::load_mut(self.accounts.position)?.set_inner(
+            *self.accounts.pool.key(),
+            *self.accounts.owner.key(),
+            self.instructions.tick_lower,
+            self.instructions.tick_upper,
+        );
+
+        Ok(())
+    }
+}
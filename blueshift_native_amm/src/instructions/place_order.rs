@@ -0,0 +1,211 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::find_program_address,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    error::PinocchioError,
+    instructions::{
+        AccountCheck, AssociatedTokenAccount, AssociatedTokenAccountCheck,
+        AssociatedTokenAccountInit, ProgramAccount, ProgramAccountInit, SignerAccount,
+    },
+    seeds,
+    state::{AmmState, Config, LimitOrder},
+    token_interface::{self, TransferChecked},
+};
+
+pub struct PlaceOrderAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+
+    // the pool mint the order sells
+    pub mint_in: &'a AccountInfo,
+
+    pub order: &'a AccountInfo,
+    // the order's associated token account of `mint_in`, it escrows the tokens for sale
+    pub order_vault: &'a AccountInfo,
+    pub owner_ata_in: &'a AccountInfo,
+
+    pub token_program_in: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    pub associated_token_program: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for PlaceOrderAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [owner, config, mint_in, order, order_vault, owner_ata_in, token_program_in, system_program, associated_token_program] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(owner)?;
+
+        Config::load(config)?.check_address(config.key())?;
+
+        AssociatedTokenAccount::check(owner_ata_in, owner, mint_in)?;
+        token_interface::check_token_program(token_program_in, mint_in)?;
+
+        Ok(Self {
+            owner,
+            config,
+            mint_in,
+            order,
+            order_vault,
+            owner_ata_in,
+            token_program_in,
+            system_program,
+            associated_token_program,
+        })
+    }
+}
+
+pub struct PlaceOrderInstructions {
+    pub seed: u64,
+    pub amount: u64,
+    pub price: u128,
+}
+
+impl<'a> TryFrom<&'a [u8]> for PlaceOrderInstructions {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<u64>() * 2 + size_of::<u128>() {
+            return Err(ProgramError::InvalidInstructionData);
+        };
+
+        let seed = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let amount = u64::from_le_bytes(data[8..16].try_into().unwrap());
+        let price = u128::from_le_bytes(data[16..32].try_into().unwrap());
+
+        if amount == 0 || price == 0 {
+            return Err(PinocchioError::InvalidAmount.into());
+        }
+
+        // the escrowed amount is at most `amount`, so an order that passes here can always be
+        // priced when it is filled
+        if LimitOrder::min_out_at(amount, price).is_none() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            seed,
+            amount,
+            price,
+        })
+    }
+}
+
+// escrows tokens of one side of a pool in a new limit order. the side is the one `mint_in`
+// belongs to, the price is in q64.64 output tokens per input token
+pub struct PlaceOrder<'a> {
+    pub accounts: PlaceOrderAccounts<'a>,
+    pub instructions: PlaceOrderInstructions,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for PlaceOrder<'a> {
+    type Error = ProgramError;
+
+    fn try_from((accounts, data): (&'a [AccountInfo], &'a [u8])) -> Result<Self, Self::Error> {
+        let accounts = PlaceOrderAccounts::try_from(accounts)?;
+        let instructions = PlaceOrderInstructions::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instructions,
+        })
+    }
+}
+
+impl<'a> PlaceOrder<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &32;
+
+    pub fn process(&self) -> ProgramResult {
+        let is_x = {
+            let config = Config::load(self.accounts.config)?;
+
+            // orders are only filled by swapping through the pool, don't take them while it
+            // can't swap
+            config.check_state(&[AmmState::Initialized])?;
+
+            match self.accounts.mint_in.key() {
+                mint if mint == config.mint_x() => true,
+                mint if mint == config.mint_y() => false,
+                _ => return Err(ProgramError::InvalidAccountData),
+            }
+        };
+
+        let seed_binding = self.instructions.seed.to_le_bytes();
+
+        let (order_pda, bump) = find_program_address(
+            &seeds::order(
+                self.accounts.config.key(),
+                self.accounts.owner.key(),
+                &seed_binding,
+            ),
+            &crate::ID,
+        );
+
+        if &order_pda != self.accounts.order.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let bump_binding = [bump];
+        let order_seeds = seeds::order_signer(
+            self.accounts.config.key(),
+            self.accounts.owner.key(),
+            &seed_binding,
+            &bump_binding,
+        );
+
+        ProgramAccount::init::<LimitOrder>(
+            self.accounts.owner,
+            self.accounts.order,
+            &order_seeds,
+            LimitOrder::LEN,
+        )?;
+
+        AssociatedTokenAccount::init(
+            self.accounts.order_vault,
+            self.accounts.mint_in,
+            self.accounts.owner,
+            self.accounts.order,
+            self.accounts.system_program,
+            self.accounts.token_program_in,
+        )?;
+
+        TransferChecked {
+            from: self.accounts.owner_ata_in,
+            mint: self.accounts.mint_in,
+            to: self.accounts.order_vault,
+            authority: self.accounts.owner,
+            amount: self.instructions.amount,
+            decimals: token_interface::mint(self.accounts.mint_in)?.decimals(),
+            token_program: self.accounts.token_program_in.key(),
+        }
+        .invoke()?;
+
+        // with token-2022 transfer fees the order only sells what reached the escrow
+        let amount = token_interface::transfer_fee(self.accounts.mint_in, Clock::get()?.epoch)?
+            .amount_received(self.instructions.amount)
+            .ok_or(PinocchioError::MathOverflow)?;
+
+        LimitOrder::load_mut(self.accounts.order)?.set_inner(
+            *self.accounts.config.key(),
+            *self.accounts.owner.key(),
+            *self.accounts.mint_in.key(),
+            self.instructions.seed,
+            is_x,
+            amount,
+            self.instructions.price,
+            bump,
+        );
+
+        Ok(())
+    }
+}
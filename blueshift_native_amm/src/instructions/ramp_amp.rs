@@ -0,0 +1,91 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    instructions::{AccountCheck, SignerAccount},
+    state::Config,
+};
+
+pub struct RampAmpAccounts<'a> {
+    pub authority: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for RampAmpAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [authority, config] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(authority)?;
+
+        Ok(Self { authority, config })
+    }
+}
+
+pub struct RampAmpInstructions {
+    pub target_amp: u64,
+    pub stop_timestamp: i64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for RampAmpInstructions {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<u64>() + size_of::<i64>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let target_amp = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let stop_timestamp = i64::from_le_bytes(data[8..16].try_into().unwrap());
+
+        Ok(Self {
+            target_amp,
+            stop_timestamp,
+        })
+    }
+}
+
+// moves the stableswap amplification towards a new value over time instead of in one jump,
+// a sudden change would let anyone arbitrage the lps
+pub struct RampAmp<'a> {
+    pub accounts: RampAmpAccounts<'a>,
+    pub instructions: RampAmpInstructions,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for RampAmp<'a> {
+    type Error = ProgramError;
+
+    fn try_from((accounts, data): (&'a [AccountInfo], &'a [u8])) -> Result<Self, Self::Error> {
+        let accounts = RampAmpAccounts::try_from(accounts)?;
+        let instructions = RampAmpInstructions::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instructions,
+        })
+    }
+}
+
+impl<'a> RampAmp<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &13;
+
+    pub fn process(&self) -> ProgramResult {
+        let mut config = Config::load_mut(self.accounts.config)?;
+
+        config.check_authority(self.accounts.authority.key())?;
+        config.ramp_amp(
+            self.instructions.target_amp,
+            Clock::get()?.unix_timestamp,
+            self.instructions.stop_timestamp,
+        )?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,362 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::Signer,
+    program_error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    curve,
+    error::PinocchioError,
+    instructions::{
+        AccountCheck, AssociatedTokenAccount, AssociatedTokenAccountCheck, SignerAccount,
+    },
+    state::{AmmState, Config},
+    token_interface::{self, TransferChecked},
+};
+
+// the pool accounts of one step of a route, in the direction the tokens flow through it
+pub struct RouteHop<'a> {
+    pub config: &'a AccountInfo,
+
+    pub mint_in: &'a AccountInfo,
+    pub mint_out: &'a AccountInfo,
+
+    pub vault_in: &'a AccountInfo,
+    pub vault_out: &'a AccountInfo,
+
+    pub token_program_in: &'a AccountInfo,
+    pub token_program_out: &'a AccountInfo,
+
+    pub is_x: bool,
+}
+
+impl<'a> RouteHop<'a> {
+    pub const ACCOUNTS_LEN: usize = 7;
+
+    fn new(accounts: &'a [AccountInfo], is_x: bool) -> Result<Self, ProgramError> {
+        let [config, mint_in, mint_out, vault_in, vault_out, token_program_in, token_program_out] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self {
+            config,
+            mint_in,
+            mint_out,
+            vault_in,
+            vault_out,
+            token_program_in,
+            token_program_out,
+            is_x,
+        })
+    }
+
+    // the mints and vaults must be the ones the pool was created with, in swap direction
+    fn check(&self) -> Result<(), ProgramError> {
+        let config = Config::load(self.config)?;
+        config.check_address(self.config.key())?;
+
+        let (mint_in, mint_out, vault_in, vault_out) = match self.is_x {
+            true => (
+                config.mint_x(),
+                config.mint_y(),
+                config.mint_x_vault(),
+                config.mint_y_vault(),
+            ),
+            false => (
+                config.mint_y(),
+                config.mint_x(),
+                config.mint_y_vault(),
+                config.mint_x_vault(),
+            ),
+        };
+
+        if mint_in != self.mint_in.key() || mint_out != self.mint_out.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if vault_in != self.vault_in.key() || vault_out != self.vault_out.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        token_interface::check_token_program(self.token_program_in, self.mint_in)?;
+        token_interface::check_token_program(self.token_program_out, self.mint_out)?;
+
+        Ok(())
+    }
+}
+
+pub struct RouteSwapAccounts<'a> {
+    pub user: &'a AccountInfo,
+
+    pub user_in_ata: &'a AccountInfo,
+    pub user_out_ata: &'a AccountInfo,
+
+    // `RouteHop::ACCOUNTS_LEN` accounts per hop, in route order
+    pub hops: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for RouteSwapAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [user, user_in_ata, user_out_ata, hops @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(user)?;
+
+        if hops.is_empty() || hops.len() % RouteHop::ACCOUNTS_LEN != 0 {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        if hops.len() / RouteHop::ACCOUNTS_LEN > RouteSwap::MAX_HOPS {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        Ok(Self {
+            user,
+            user_in_ata,
+            user_out_ata,
+            hops,
+        })
+    }
+}
+
+pub struct RouteSwapInstructions<'a> {
+    pub amount: u64,
+    pub min_out: u64,
+    pub expiration: i64,
+    // one byte per hop, 1 when the hop sells the pool's x token
+    pub directions: &'a [u8],
+}
+
+impl<'a> TryFrom<&'a [u8]> for RouteSwapInstructions<'a> {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() <= size_of::<u64>() * 2 + size_of::<i64>() {
+            return Err(ProgramError::InvalidInstructionData);
+        };
+
+        let amount = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let min_out = u64::from_le_bytes(data[8..16].try_into().unwrap());
+        let expiration = i64::from_le_bytes(data[16..24].try_into().unwrap());
+        let directions = &data[24..];
+
+        if directions.iter().any(|direction| *direction > 1) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        if amount == 0 {
+            return Err(PinocchioError::InvalidAmount.into());
+        }
+
+        Ok(Self {
+            amount,
+            min_out,
+            expiration,
+            directions,
+        })
+    }
+}
+
+// swaps through several pools in a row. the output of each hop goes straight into the vault of
+// the next pool, only the first input and the last output touch the user's token accounts.
+// oracles of the pools along the way are not written to
+pub struct RouteSwap<'a> {
+    pub accounts: RouteSwapAccounts<'a>,
+    pub instructions: RouteSwapInstructions<'a>,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for RouteSwap<'a> {
+    type Error = ProgramError;
+
+    fn try_from((accounts, data): (&'a [AccountInfo], &'a [u8])) -> Result<Self, Self::Error> {
+        let accounts = RouteSwapAccounts::try_from(accounts)?;
+        let instructions = RouteSwapInstructions::try_from(data)?;
+
+        let route_swap = Self {
+            accounts,
+            instructions,
+        };
+
+        if route_swap.instructions.directions.len()
+            != route_swap.accounts.hops.len() / RouteHop::ACCOUNTS_LEN
+        {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let mut previous: Option<RouteHop> = None;
+
+        for hop in route_swap.hops() {
+            let hop = hop?;
+            hop.check()?;
+
+            match &previous {
+                // the route starts from the user's account of the first input token
+                None => {
+                    AssociatedTokenAccount::check(
+                        route_swap.accounts.user_in_ata,
+                        route_swap.accounts.user,
+                        hop.mint_in,
+                    )?;
+                }
+                // each hop has to sell what the one before it bought
+                Some(previous) => {
+                    if previous.mint_out.key() != hop.mint_in.key() {
+                        return Err(ProgramError::InvalidAccountData);
+                    }
+                }
+            }
+
+            previous = Some(hop);
+        }
+
+        if let Some(last) = previous {
+            AssociatedTokenAccount::check(
+                route_swap.accounts.user_out_ata,
+                route_swap.accounts.user,
+                last.mint_out,
+            )?;
+        }
+
+        Ok(route_swap)
+    }
+}
+
+impl<'a> RouteSwap<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &22;
+
+    pub const MAX_HOPS: usize = 4;
+
+    pub fn process(&self) -> ProgramResult {
+        let clock = Clock::get()?;
+
+        if clock.unix_timestamp > self.instructions.expiration {
+            return Err(PinocchioError::Expired.into());
+        }
+
+        let mut previous: Option<RouteHop> = None;
+        let mut amount = self.instructions.amount;
+
+        for hop in self.hops() {
+            let hop = hop?;
+            // token-2022 transfer fees are withheld on every hop, each pool prices what arrives
+            let amount_received = token_interface::transfer_fee(hop.mint_in, clock.epoch)?
+                .amount_received(amount)
+                .ok_or(PinocchioError::MathOverflow)?;
+
+            let withdraw = self.quote(&hop, amount_received, &clock)?;
+
+            match &previous {
+                None => TransferChecked {
+                    from: self.accounts.user_in_ata,
+                    mint: hop.mint_in,
+                    to: hop.vault_in,
+                    authority: self.accounts.user,
+                    amount,
+                    decimals: token_interface::mint(hop.mint_in)?.decimals(),
+                    token_program: hop.token_program_in.key(),
+                }
+                .invoke()?,
+                // the previous pool pays its output straight into this pool's vault
+                Some(previous) => {
+                    let config = Config::load(previous.config)?;
+                    let config_seeds = config.signer_seeds();
+
+                    TransferChecked {
+                        from: previous.vault_out,
+                        mint: hop.mint_in,
+                        to: hop.vault_in,
+                        authority: previous.config,
+                        amount,
+                        decimals: token_interface::mint(hop.mint_in)?.decimals(),
+                        token_program: hop.token_program_in.key(),
+                    }
+                    .invoke_signed(&[Signer::from(&config_seeds)])?
+                }
+            }
+
+            amount = withdraw;
+            previous = Some(hop);
+        }
+
+        let last = previous.ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+        // only what reaches the user counts towards the minimum
+        let amount_received = token_interface::transfer_fee(last.mint_out, clock.epoch)?
+            .amount_received(amount)
+            .ok_or(PinocchioError::MathOverflow)?;
+
+        if amount_received < self.instructions.min_out {
+            return Err(PinocchioError::SlipageExceeded.into());
+        }
+
+        let config = Config::load(last.config)?;
+        let config_seeds = config.signer_seeds();
+
+        TransferChecked {
+            from: last.vault_out,
+            mint: last.mint_out,
+            to: self.accounts.user_out_ata,
+            authority: last.config,
+            amount,
+            decimals: token_interface::mint(last.mint_out)?.decimals(),
+            token_program: last.token_program_out.key(),
+        }
+        .invoke_signed(&[Signer::from(&config_seeds)])?;
+
+        Ok(())
+    }
+
+    fn hops(&self) -> impl Iterator<Item = Result<RouteHop<'a>, ProgramError>> + '_ {
+        self.accounts
+            .hops
+            .chunks_exact(RouteHop::ACCOUNTS_LEN)
+            .zip(self.instructions.directions)
+            .map(|(accounts, direction)| RouteHop::new(accounts, *direction == 1))
+    }
+
+    // prices one hop against the pool's current reserves and books its fees, returns the
+    // amount the pool pays out. intermediate hops have no slippage bound of their own
+    fn quote(&self, hop: &RouteHop, amount: u64, clock: &Clock) -> Result<u64, ProgramError> {
+        let mut config = Config::load_mut(hop.config)?;
+
+        // swaps are only allowed while the pool is fully active
+        config.check_state(&[AmmState::Initialized])?;
+
+        let (reserve_x, reserve_y) = config.reserves();
+
+        // the price accumulators only ever see the reserves from before the swap
+        config.update_price_accumulators(reserve_x, reserve_y, clock);
+
+        let swap_result = curve::swap(
+            &config,
+            reserve_x,
+            reserve_y,
+            hop.is_x,
+            amount,
+            0,
+            clock.unix_timestamp,
+        )?;
+
+        // part of the swap fee is set aside for the protocol, it stays in the input vault
+        let protocol_fee = config.protocol_fee_amount(swap_result.fee)?;
+        config.accrue_protocol_fees(hop.is_x, protocol_fee)?;
+
+        config.swap_reserves(
+            hop.is_x,
+            swap_result.deposit - protocol_fee,
+            swap_result.withdraw,
+        )?;
+
+        let new_reserves = config.reserves();
+        config.update_volatility((reserve_x, reserve_y), new_reserves, clock.unix_timestamp);
+
+        Ok(swap_result.withdraw)
+    }
+}
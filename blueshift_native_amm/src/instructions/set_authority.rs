@@ -0,0 +1,84 @@
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+use crate::{
+    error::PinocchioError,
+    instructions::{AccountCheck, SignerAccount},
+    state::Config,
+};
+
+pub struct SetAuthorityAccounts<'a> {
+    pub authority: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SetAuthorityAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [authority, config] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(authority)?;
+
+        Ok(Self { authority, config })
+    }
+}
+
+pub struct SetAuthorityInstructions {
+    pub new_authority: Pubkey,
+}
+
+impl<'a> TryFrom<&'a [u8]> for SetAuthorityInstructions {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<Pubkey>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let new_authority: Pubkey = data.try_into().unwrap();
+
+        // renouncing goes through its own instruction so it can't happen by accident
+        if new_authority == Pubkey::default() {
+            return Err(PinocchioError::InvalidAuthority.into());
+        }
+
+        Ok(Self { new_authority })
+    }
+}
+
+// first step of the authority rotation, the proposed key has to accept before it takes over
+pub struct SetAuthority<'a> {
+    pub accounts: SetAuthorityAccounts<'a>,
+    pub instructions: SetAuthorityInstructions,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for SetAuthority<'a> {
+    type Error = ProgramError;
+
+    fn try_from((accounts, data): (&'a [AccountInfo], &'a [u8])) -> Result<Self, Self::Error> {
+        let accounts = SetAuthorityAccounts::try_from(accounts)?;
+        let instructions = SetAuthorityInstructions::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instructions,
+        })
+    }
+}
+
+impl<'a> SetAuthority<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &5;
+
+    pub fn process(&self) -> ProgramResult {
+        let mut config = Config::load_mut(self.accounts.config)?;
+
+        config.check_authority(self.accounts.authority.key())?;
+        config.set_pending_authority(self.instructions.new_authority);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,114 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    curve::{self, fee_amount},
+    error::PinocchioError,
+    state::Config,
+    token_interface,
+};
+
+pub struct SettleFlashSwapAccounts<'a> {
+    pub config: &'a AccountInfo,
+
+    pub vault_x: &'a AccountInfo,
+    pub vault_y: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SettleFlashSwapAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [config, vault_x, vault_y] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        let config_data = Config::load(config)?;
+        config_data.check_address(config.key())?;
+
+        if config_data.mint_x_vault() != vault_x.key()
+            || config_data.mint_y_vault() != vault_y.key()
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(Self {
+            config,
+            vault_x,
+            vault_y,
+        })
+    }
+}
+
+// closes an open flash swap. whatever the vaults hold on top of the reserves left after the
+// tokens went out is treated as the swap input and pays the regular swap fee, the rest has to
+// keep the curve invariant. anyone can settle, the invariant is the only thing that matters
+pub struct SettleFlashSwap<'a> {
+    pub accounts: SettleFlashSwapAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SettleFlashSwap<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let accounts = SettleFlashSwapAccounts::try_from(accounts)?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> SettleFlashSwap<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &24;
+
+    pub fn process(&self) -> ProgramResult {
+        let mut config = Config::load_mut(self.accounts.config)?;
+        let timestamp = Clock::get()?.unix_timestamp;
+
+        let (reserve_x, reserve_y) = {
+            let vault_x = token_interface::token_account(self.accounts.vault_x)?;
+            let vault_y = token_interface::token_account(self.accounts.vault_y)?;
+
+            config.vault_reserves(vault_x.amount(), vault_y.amount())?
+        };
+
+        // the pool is locked during a flash swap, the stored reserves are still the ones from
+        // before it
+        let (old_reserve_x, old_reserve_y) = config.reserves();
+        let (amount_x, amount_y) = config.flash_amounts();
+
+        // the input is whatever came back on top of the reserves left after the tokens went out
+        let amount_in_x = reserve_x.saturating_sub(old_reserve_x - amount_x);
+        let amount_in_y = reserve_y.saturating_sub(old_reserve_y - amount_y);
+
+        let fee = config.swap_fee(timestamp);
+        let fee_x = fee_amount(amount_in_x, fee).ok_or(PinocchioError::MathOverflow)?;
+        let fee_y = fee_amount(amount_in_y, fee).ok_or(PinocchioError::MathOverflow)?;
+
+        curve::check_invariant(
+            &config,
+            (old_reserve_x, old_reserve_y),
+            (reserve_x - fee_x, reserve_y - fee_y),
+            timestamp,
+        )?;
+
+        // part of the swap fee is set aside for the protocol, it stays in the input vault
+        let protocol_fee_x = config.protocol_fee_amount(fee_x)?;
+        config.accrue_protocol_fees(true, protocol_fee_x)?;
+
+        let protocol_fee_y = config.protocol_fee_amount(fee_y)?;
+        config.accrue_protocol_fees(false, protocol_fee_y)?;
+
+        // everything that came back in belongs to the lps from now on
+        config.set_reserves(reserve_x - protocol_fee_x, reserve_y - protocol_fee_y);
+
+        // a settled flash swap moves the price like a regular swap
+        let new_reserves = config.reserves();
+        config.update_volatility((old_reserve_x, old_reserve_y), new_reserves, timestamp);
+
+        config.close_flash_swap()
+    }
+}
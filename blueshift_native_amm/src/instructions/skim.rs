@@ -0,0 +1,143 @@
+use pinocchio::{
+    account_info::AccountInfo, instruction::Signer, program_error::ProgramError, ProgramResult,
+};
+
+use crate::{
+    error::PinocchioError,
+    state::{AmmState, Config},
+    token_interface::{self, TransferChecked},
+};
+
+pub struct SkimAccounts<'a> {
+    pub config: &'a AccountInfo,
+
+    pub mint_x: &'a AccountInfo,
+    pub mint_y: &'a AccountInfo,
+
+    pub vault_x: &'a AccountInfo,
+    pub vault_y: &'a AccountInfo,
+
+    // any token accounts of the right mints, the excess is sent there
+    pub recipient_x: &'a AccountInfo,
+    pub recipient_y: &'a AccountInfo,
+
+    pub token_program_x: &'a AccountInfo,
+    pub token_program_y: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SkimAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [config, mint_x, mint_y, vault_x, vault_y, recipient_x, recipient_y, token_program_x, token_program_y] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // the mints and vaults must be the ones the pool was created with
+        let config_data = Config::load(config)?;
+        config_data.check_address(config.key())?;
+
+        if config_data.mint_x() != mint_x.key() || config_data.mint_y() != mint_y.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if config_data.mint_x_vault() != vault_x.key()
+            || config_data.mint_y_vault() != vault_y.key()
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        token_interface::check_token_program(token_program_x, mint_x)?;
+        token_interface::check_token_program(token_program_y, mint_y)?;
+
+        Ok(Self {
+            config,
+            mint_x,
+            mint_y,
+            vault_x,
+            vault_y,
+            recipient_x,
+            recipient_y,
+            token_program_x,
+            token_program_y,
+        })
+    }
+}
+
+// sends whatever the vaults hold on top of the reserves and the accrued protocol fees to the
+// recipients, the counterpart of `SyncReserves`. anyone can skim, like uniswap v2
+pub struct Skim<'a> {
+    pub accounts: SkimAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for Skim<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let accounts = SkimAccounts::try_from(accounts)?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> Skim<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &27;
+
+    pub fn process(&self) -> ProgramResult {
+        let config = Config::load(self.accounts.config)?;
+
+        // while a flash swap is open the repayment sits in the vaults on top of the reserves
+        config.check_state(&[AmmState::Initialized, AmmState::WithdrawOnly])?;
+
+        let (amount_x, amount_y) = {
+            let vault_x = token_interface::token_account(self.accounts.vault_x)?;
+            let vault_y = token_interface::token_account(self.accounts.vault_y)?;
+
+            let (vault_reserve_x, vault_reserve_y) =
+                config.vault_reserves(vault_x.amount(), vault_y.amount())?;
+            let (reserve_x, reserve_y) = config.reserves();
+
+            (
+                vault_reserve_x.saturating_sub(reserve_x),
+                vault_reserve_y.saturating_sub(reserve_y),
+            )
+        };
+
+        if amount_x == 0 && amount_y == 0 {
+            return Err(PinocchioError::InvalidAmount.into());
+        }
+
+        let config_seeds = config.signer_seeds();
+        let signer = [Signer::from(&config_seeds)];
+
+        if amount_x > 0 {
+            TransferChecked {
+                from: self.accounts.vault_x,
+                mint: self.accounts.mint_x,
+                to: self.accounts.recipient_x,
+                authority: self.accounts.config,
+                amount: amount_x,
+                decimals: token_interface::mint(self.accounts.mint_x)?.decimals(),
+                token_program: self.accounts.token_program_x.key(),
+            }
+            .invoke_signed(&signer)?;
+        }
+
+        if amount_y > 0 {
+            TransferChecked {
+                from: self.accounts.vault_y,
+                mint: self.accounts.mint_y,
+                to: self.accounts.recipient_y,
+                authority: self.accounts.config,
+                amount: amount_y,
+                decimals: token_interface::mint(self.accounts.mint_y)?.decimals(),
+                token_program: self.accounts.token_program_y.key(),
+            }
+            .invoke_signed(&signer)?;
+        }
+
+        Ok(())
+    }
+}
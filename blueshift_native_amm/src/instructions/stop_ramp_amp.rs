@@ -0,0 +1,58 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    instructions::{AccountCheck, SignerAccount},
+    state::Config,
+};
+
+pub struct StopRampAmpAccounts<'a> {
+    pub authority: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for StopRampAmpAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [authority, config] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(authority)?;
+
+        Ok(Self { authority, config })
+    }
+}
+
+// keeps the amplification wherever the running ramp currently is
+pub struct StopRampAmp<'a> {
+    pub accounts: StopRampAmpAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for StopRampAmp<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let accounts = StopRampAmpAccounts::try_from(accounts)?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> StopRampAmp<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &14;
+
+    pub fn process(&self) -> ProgramResult {
+        let mut config = Config::load_mut(self.accounts.config)?;
+
+        config.check_authority(self.accounts.authority.key())?;
+        config.stop_ramp_amp(Clock::get()?.unix_timestamp)?;
+
+        Ok(())
+    }
+}
@@ -1 +1,316 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::Signer,
+    program_error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
 
+use crate::{
+    curve,
+    error::PinocchioError,
+    instructions::{
+        optional_account, AccountCheck, AssociatedTokenAccount, AssociatedTokenAccountCheck,
+        SignerAccount,
+    },
+    state::{AmmState, Config, Oracle},
+    token_interface::{self, TransferChecked},
+};
+
+pub struct SwapAccounts<'a> {
+    pub user: &'a AccountInfo,
+
+    pub mint_x: &'a AccountInfo,
+    pub mint_y: &'a AccountInfo,
+
+    pub config: &'a AccountInfo,
+
+    pub vault_x: &'a AccountInfo,
+    pub vault_y: &'a AccountInfo,
+
+    pub user_x_ata: &'a AccountInfo,
+    pub user_y_ata: &'a AccountInfo,
+
+    pub token_program_x: &'a AccountInfo,
+    pub token_program_y: &'a AccountInfo,
+
+    pub oracle: Option<&'a AccountInfo>,
+    // token account of the input mint that receives the referral share of the swap fee
+    pub referrer: Option<&'a AccountInfo>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SwapAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [user, mint_x, mint_y, config, vault_x, vault_y, user_x_ata, user_y_ata, token_program_x, token_program_y, remaining @ ..] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // account checks
+        SignerAccount::check(user)?;
+
+        AssociatedTokenAccount::check(user_x_ata, user, mint_x)?;
+        AssociatedTokenAccount::check(user_y_ata, user, mint_y)?;
+
+        // the mints and vaults must be the ones the pool was created with
+        let config_data = Config::load(config)?;
+        config_data.check_address(config.key())?;
+
+        if config_data.mint_x() != mint_x.key() || config_data.mint_y() != mint_y.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if config_data.mint_x_vault() != vault_x.key()
+            || config_data.mint_y_vault() != vault_y.key()
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        token_interface::check_token_program(token_program_x, mint_x)?;
+        token_interface::check_token_program(token_program_y, mint_y)?;
+
+        Ok(Self {
+            user,
+            mint_x,
+            mint_y,
+            config,
+            vault_x,
+            vault_y,
+            user_x_ata,
+            user_y_ata,
+            token_program_x,
+            token_program_y,
+            oracle: optional_account(remaining.first()),
+            referrer: optional_account(remaining.get(1)),
+        })
+    }
+}
+
+pub struct SwapInstructions {
+    pub is_x: bool,
+    pub amount: u64,
+    pub min_out: u64,
+    pub expiration: i64,
+    // share of the swap fee paid to the referrer, in basis points of the fee. nothing stops a
+    // user from naming their own account as the referrer, that is intended: it works as a rebate
+    // of at most the pool's referral cap, which the authority sets knowing this
+    pub referral_fee: u16,
+}
+
+impl<'a> TryFrom<&'a [u8]> for SwapInstructions {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        const LEN: usize = size_of::<u8>() + size_of::<u64>() * 2 + size_of::<i64>();
+
+        // the referral fee is optional, swaps without a referrer can leave it out
+        let referral_fee = match data.len() {
+            LEN => 0,
+            len if len == LEN + size_of::<u16>() => u16::from_le_bytes([data[25], data[26]]),
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        if referral_fee > Config::MAX_REFERRAL_FEE {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let is_x = match data[0] {
+            0 => false,
+            1 => true,
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+        let amount = u64::from_le_bytes(data[1..9].try_into().unwrap());
+        let min_out = u64::from_le_bytes(data[9..17].try_into().unwrap());
+        let expiration = i64::from_le_bytes(data[17..25].try_into().unwrap());
+
+        if amount == 0 {
+            return Err(PinocchioError::InvalidAmount.into());
+        }
+
+        Ok(Self {
+            is_x,
+            amount,
+            min_out,
+            expiration,
+            referral_fee,
+        })
+    }
+}
+
+pub struct Swap<'a> {
+    pub accounts: SwapAccounts<'a>,
+    pub instructions: SwapInstructions,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for Swap<'a> {
+    type Error = ProgramError;
+
+    fn try_from((accounts, data): (&'a [AccountInfo], &'a [u8])) -> Result<Self, Self::Error> {
+        let accounts = SwapAccounts::try_from(accounts)?;
+        let instructions = SwapInstructions::try_from(data)?;
+
+        if instructions.referral_fee > 0 && accounts.referrer.is_none() {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        Ok(Self {
+            accounts,
+            instructions,
+        })
+    }
+}
+
+impl<'a> Swap<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &2;
+
+    pub fn process(&self) -> ProgramResult {
+        let clock = Clock::get()?;
+
+        if clock.unix_timestamp > self.instructions.expiration {
+            return Err(PinocchioError::Expired.into());
+        }
+
+        let (user_from, vault_to, mint_in, token_program_in) = match self.instructions.is_x {
+            true => (
+                self.accounts.user_x_ata,
+                self.accounts.vault_x,
+                self.accounts.mint_x,
+                self.accounts.token_program_x,
+            ),
+            false => (
+                self.accounts.user_y_ata,
+                self.accounts.vault_y,
+                self.accounts.mint_y,
+                self.accounts.token_program_y,
+            ),
+        };
+
+        let (vault_from, user_to, mint_out, token_program_out) = match self.instructions.is_x {
+            true => (
+                self.accounts.vault_y,
+                self.accounts.user_y_ata,
+                self.accounts.mint_y,
+                self.accounts.token_program_y,
+            ),
+            false => (
+                self.accounts.vault_x,
+                self.accounts.user_x_ata,
+                self.accounts.mint_x,
+                self.accounts.token_program_x,
+            ),
+        };
+
+        // token-2022 transfer fees are withheld from what arrives. the pool prices the amount it
+        // actually receives, and the user's bound is grossed up by the fee on the way out
+        let fee_in = token_interface::transfer_fee(mint_in, clock.epoch)?;
+        let fee_out = token_interface::transfer_fee(mint_out, clock.epoch)?;
+
+        let amount_received = fee_in
+            .amount_received(self.instructions.amount)
+            .ok_or(PinocchioError::MathOverflow)?;
+        let min_withdraw = fee_out
+            .inverse_fee(self.instructions.min_out)
+            .and_then(|fee| self.instructions.min_out.checked_add(fee))
+            .ok_or(PinocchioError::MathOverflow)?;
+
+        let (swap_result, referral_amount) = {
+            let mut config = Config::load_mut(self.accounts.config)?;
+
+            // swaps are only allowed while the pool is fully active
+            config.check_state(&[AmmState::Initialized])?;
+
+            if self.instructions.referral_fee > config.max_referral_fee() {
+                return Err(PinocchioError::InvalidReferralFee.into());
+            }
+
+            let (reserve_x, reserve_y) = config.reserves();
+
+            // the price accumulators only ever see the reserves from before the swap
+            config.update_price_accumulators(reserve_x, reserve_y, &clock);
+
+            if let Some(oracle) = self.accounts.oracle {
+                Oracle::load_mut(oracle)?.record(self.accounts.config.key(), &config)?;
+            }
+
+            let swap_result = curve::swap(
+                &config,
+                reserve_x,
+                reserve_y,
+                self.instructions.is_x,
+                amount_received,
+                min_withdraw,
+                clock.unix_timestamp,
+            )?;
+
+            // part of the swap fee is set aside for the protocol, it stays in the input vault
+            let protocol_fee = config.protocol_fee_amount(swap_result.fee)?;
+            config.accrue_protocol_fees(self.instructions.is_x, protocol_fee)?;
+
+            // the referrer's part leaves the vault below, neither it nor the protocol fee is
+            // added to the reserves
+            let referral_amount =
+                config.referral_fee_amount(swap_result.fee, self.instructions.referral_fee)?;
+
+            let amount_in = swap_result
+                .deposit
+                .checked_sub(protocol_fee)
+                .and_then(|amount| amount.checked_sub(referral_amount))
+                .ok_or(PinocchioError::MathOverflow)?;
+
+            config.swap_reserves(self.instructions.is_x, amount_in, swap_result.withdraw)?;
+
+            // the dynamic fee follows how far swaps move the price
+            let new_reserves = config.reserves();
+            config.update_volatility((reserve_x, reserve_y), new_reserves, clock.unix_timestamp);
+
+            (swap_result, referral_amount)
+        };
+
+        let config = Config::load(self.accounts.config)?;
+        let config_seeds = config.signer_seeds();
+        let signer = [Signer::from(&config_seeds)];
+
+        // transferring the input tokens from the user into the pool, the curve took all of what
+        // arrives so the user sends the full amount including the transfer fee
+        TransferChecked {
+            from: user_from,
+            mint: mint_in,
+            to: vault_to,
+            authority: self.accounts.user,
+            amount: self.instructions.amount,
+            decimals: token_interface::mint(mint_in)?.decimals(),
+            token_program: token_program_in.key(),
+        }
+        .invoke()?;
+
+        // transferring the output tokens from the pool to the user
+        TransferChecked {
+            from: vault_from,
+            mint: mint_out,
+            to: user_to,
+            authority: self.accounts.config,
+            amount: swap_result.withdraw,
+            decimals: token_interface::mint(mint_out)?.decimals(),
+            token_program: token_program_out.key(),
+        }
+        .invoke_signed(&signer)?;
+
+        if let Some(referrer) = self.accounts.referrer.filter(|_| referral_amount > 0) {
+            TransferChecked {
+                from: vault_to,
+                mint: mint_in,
+                to: referrer,
+                authority: self.accounts.config,
+                amount: referral_amount,
+                decimals: token_interface::mint(mint_in)?.decimals(),
+                token_program: token_program_in.key(),
+            }
+            .invoke_signed(&signer)?;
+        }
+
+        Ok(())
+    }
+}
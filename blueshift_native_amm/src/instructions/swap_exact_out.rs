@@ -0,0 +1,204 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::Signer,
+    program_error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    curve,
+    error::PinocchioError,
+    instructions::SwapAccounts,
+    state::{AmmState, Config, Oracle},
+    token_interface::{self, TransferChecked},
+};
+
+pub struct SwapExactOutInstructions {
+    pub is_x: bool,
+    pub amount_out: u64,
+    pub max_in: u64,
+    pub expiration: i64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for SwapExactOutInstructions {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<u8>() + size_of::<u64>() * 2 + size_of::<i64>() {
+            return Err(ProgramError::InvalidInstructionData);
+        };
+
+        let is_x = match data[0] {
+            0 => false,
+            1 => true,
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+        let amount_out = u64::from_le_bytes(data[1..9].try_into().unwrap());
+        let max_in = u64::from_le_bytes(data[9..17].try_into().unwrap());
+        let expiration = i64::from_le_bytes(data[17..25].try_into().unwrap());
+
+        if amount_out == 0 {
+            return Err(PinocchioError::InvalidAmount.into());
+        }
+
+        Ok(Self {
+            is_x,
+            amount_out,
+            max_in,
+            expiration,
+        })
+    }
+}
+
+// swap for an exact amount of the output token, spending at most `max_in` of the input token.
+// takes the same accounts as a regular swap
+pub struct SwapExactOut<'a> {
+    pub accounts: SwapAccounts<'a>,
+    pub instructions: SwapExactOutInstructions,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for SwapExactOut<'a> {
+    type Error = ProgramError;
+
+    fn try_from((accounts, data): (&'a [AccountInfo], &'a [u8])) -> Result<Self, Self::Error> {
+        let accounts = SwapAccounts::try_from(accounts)?;
+        let instructions = SwapExactOutInstructions::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instructions,
+        })
+    }
+}
+
+impl<'a> SwapExactOut<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &21;
+
+    pub fn process(&self) -> ProgramResult {
+        let clock = Clock::get()?;
+
+        if clock.unix_timestamp > self.instructions.expiration {
+            return Err(PinocchioError::Expired.into());
+        }
+
+        let (user_from, vault_to, mint_in, token_program_in) = match self.instructions.is_x {
+            true => (
+                self.accounts.user_x_ata,
+                self.accounts.vault_x,
+                self.accounts.mint_x,
+                self.accounts.token_program_x,
+            ),
+            false => (
+                self.accounts.user_y_ata,
+                self.accounts.vault_y,
+                self.accounts.mint_y,
+                self.accounts.token_program_y,
+            ),
+        };
+
+        let (vault_from, user_to, mint_out, token_program_out) = match self.instructions.is_x {
+            true => (
+                self.accounts.vault_y,
+                self.accounts.user_y_ata,
+                self.accounts.mint_y,
+                self.accounts.token_program_y,
+            ),
+            false => (
+                self.accounts.vault_x,
+                self.accounts.user_x_ata,
+                self.accounts.mint_x,
+                self.accounts.token_program_x,
+            ),
+        };
+
+        // with token-2022 transfer fees the pool sends enough for the user to end up with
+        // `amount_out`, and the user sends enough for the pool to receive the quoted input
+        let fee_in = token_interface::transfer_fee(mint_in, clock.epoch)?;
+        let fee_out = token_interface::transfer_fee(mint_out, clock.epoch)?;
+
+        let withdraw = fee_out
+            .inverse_fee(self.instructions.amount_out)
+            .and_then(|fee| self.instructions.amount_out.checked_add(fee))
+            .ok_or(PinocchioError::MathOverflow)?;
+
+        let deposit = {
+            let mut config = Config::load_mut(self.accounts.config)?;
+
+            // swaps are only allowed while the pool is fully active
+            config.check_state(&[AmmState::Initialized])?;
+
+            let (reserve_x, reserve_y) = config.reserves();
+
+            // the price accumulators only ever see the reserves from before the swap
+            config.update_price_accumulators(reserve_x, reserve_y, &clock);
+
+            if let Some(oracle) = self.accounts.oracle {
+                Oracle::load_mut(oracle)?.record(self.accounts.config.key(), &config)?;
+            }
+
+            // the bound is checked again below once the input transfer fee is added
+            let swap_result = curve::swap_exact_out(
+                &config,
+                reserve_x,
+                reserve_y,
+                self.instructions.is_x,
+                withdraw,
+                self.instructions.max_in,
+                clock.unix_timestamp,
+            )?;
+
+            // part of the swap fee is set aside for the protocol, it stays in the input vault
+            let protocol_fee = config.protocol_fee_amount(swap_result.fee)?;
+            config.accrue_protocol_fees(self.instructions.is_x, protocol_fee)?;
+
+            config.swap_reserves(
+                self.instructions.is_x,
+                swap_result.deposit - protocol_fee,
+                swap_result.withdraw,
+            )?;
+
+            let new_reserves = config.reserves();
+            config.update_volatility((reserve_x, reserve_y), new_reserves, clock.unix_timestamp);
+
+            fee_in
+                .inverse_fee(swap_result.deposit)
+                .and_then(|fee| swap_result.deposit.checked_add(fee))
+                .ok_or(PinocchioError::MathOverflow)?
+        };
+
+        if deposit > self.instructions.max_in {
+            return Err(PinocchioError::SlipageExceeded.into());
+        }
+
+        let config = Config::load(self.accounts.config)?;
+        let config_seeds = config.signer_seeds();
+        let signer = [Signer::from(&config_seeds)];
+
+        // transferring the input tokens from the user into the pool
+        TransferChecked {
+            from: user_from,
+            mint: mint_in,
+            to: vault_to,
+            authority: self.accounts.user,
+            amount: deposit,
+            decimals: token_interface::mint(mint_in)?.decimals(),
+            token_program: token_program_in.key(),
+        }
+        .invoke()?;
+
+        // transferring the output tokens from the pool to the user
+        TransferChecked {
+            from: vault_from,
+            mint: mint_out,
+            to: user_to,
+            authority: self.accounts.config,
+            amount: withdraw,
+            decimals: token_interface::mint(mint_out)?.decimals(),
+            token_program: token_program_out.key(),
+        }
+        .invoke_signed(&signer)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,86 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    state::{AmmState, Config},
+    token_interface,
+};
+
+pub struct SyncReservesAccounts<'a> {
+    pub config: &'a AccountInfo,
+
+    pub vault_x: &'a AccountInfo,
+    pub vault_y: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SyncReservesAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [config, vault_x, vault_y] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        let config_data = Config::load(config)?;
+        config_data.check_address(config.key())?;
+
+        if config_data.mint_x_vault() != vault_x.key()
+            || config_data.mint_y_vault() != vault_y.key()
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(Self {
+            config,
+            vault_x,
+            vault_y,
+        })
+    }
+}
+
+// sets the reserves to what the vaults actually hold, less the accrued protocol fees. tokens
+// sent straight into the vaults are handed to the lps. anyone can sync, like uniswap v2
+pub struct SyncReserves<'a> {
+    pub accounts: SyncReservesAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SyncReserves<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let accounts = SyncReservesAccounts::try_from(accounts)?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> SyncReserves<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &26;
+
+    pub fn process(&self) -> ProgramResult {
+        let mut config = Config::load_mut(self.accounts.config)?;
+
+        // the vaults don't match the reserves while a flash swap is open
+        config.check_state(&[AmmState::Initialized, AmmState::WithdrawOnly])?;
+
+        let (vault_reserve_x, vault_reserve_y) = {
+            let vault_x = token_interface::token_account(self.accounts.vault_x)?;
+            let vault_y = token_interface::token_account(self.accounts.vault_y)?;
+
+            config.vault_reserves(vault_x.amount(), vault_y.amount())?
+        };
+
+        let (reserve_x, reserve_y) = config.reserves();
+
+        // the price accumulators only ever see the reserves from before the sync
+        config.update_price_accumulators(reserve_x, reserve_y, &Clock::get()?);
+
+        config.set_reserves(vault_reserve_x, vault_reserve_y);
+
+        Ok(())
+    }
+}
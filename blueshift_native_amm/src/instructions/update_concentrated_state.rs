@@ -0,0 +1,82 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::{
+    error::PinocchioError,
+    instructions::{AccountCheck, SignerAccount},
+    state::{AmmState, ConcentratedPool},
+};
+
+pub struct UpdateConcentratedStateAccounts<'a> {
+    pub authority: &'a AccountInfo,
+    pub pool: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for UpdateConcentratedStateAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [authority, pool] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(authority)?;
+
+        Ok(Self { authority, pool })
+    }
+}
+
+pub struct UpdateConcentratedStateInstructions {
+    pub state: AmmState,
+}
+
+impl<'a> TryFrom<&'a [u8]> for UpdateConcentratedStateInstructions {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let [state] = data else {
+            return Err(ProgramError::InvalidInstructionData);
+        };
+
+        let state = AmmState::try_from(*state)?;
+
+        // concentrated pools have no flash swaps
+        if state == AmmState::Uninitialized || state == AmmState::FlashSwap {
+            return Err(PinocchioError::InvalidPoolState.into());
+        }
+
+        Ok(Self { state })
+    }
+}
+
+// pauses or resumes a concentrated pool, same states as `UpdateState`
+pub struct UpdateConcentratedState<'a> {
+    pub accounts: UpdateConcentratedStateAccounts<'a>,
+    pub instructions: UpdateConcentratedStateInstructions,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for UpdateConcentratedState<'a> {
+    type Error = ProgramError;
+
+    fn try_from((accounts, data): (&'a [AccountInfo], &'a [u8])) -> Result<Self, Self::Error> {
+        let accounts = UpdateConcentratedStateAccounts::try_from(accounts)?;
+        let instructions = UpdateConcentratedStateInstructions::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instructions,
+        })
+    }
+}
+
+impl<'a> UpdateConcentratedState<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &35;
+
+    pub fn process(&self) -> ProgramResult {
+        let mut pool = ConcentratedPool::load_mut(self.accounts.pool)?;
+
+        pool.check_authority(self.accounts.authority.key())?;
+        pool.set_state(self.instructions.state as u8);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,93 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::{
+    instructions::{AccountCheck, SignerAccount},
+    state::Config,
+};
+
+pub struct UpdateDynamicFeeAccounts<'a> {
+    pub authority: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for UpdateDynamicFeeAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [authority, config] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(authority)?;
+
+        Ok(Self { authority, config })
+    }
+}
+
+pub struct UpdateDynamicFeeInstructions {
+    pub enabled: bool,
+    pub min_fee: u16,
+    pub max_fee: u16,
+}
+
+impl<'a> TryFrom<&'a [u8]> for UpdateDynamicFeeInstructions {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<u8>() + size_of::<u16>() * 2 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let enabled = match data[0] {
+            0 => false,
+            1 => true,
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+        let min_fee = u16::from_le_bytes([data[1], data[2]]);
+        let max_fee = u16::from_le_bytes([data[3], data[4]]);
+
+        Ok(Self {
+            enabled,
+            min_fee,
+            max_fee,
+        })
+    }
+}
+
+// switches the pool between the static fee and the volatility based one, and sets the bounds
+// the dynamic fee is kept between
+pub struct UpdateDynamicFee<'a> {
+    pub accounts: UpdateDynamicFeeAccounts<'a>,
+    pub instructions: UpdateDynamicFeeInstructions,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for UpdateDynamicFee<'a> {
+    type Error = ProgramError;
+
+    fn try_from((accounts, data): (&'a [AccountInfo], &'a [u8])) -> Result<Self, Self::Error> {
+        let accounts = UpdateDynamicFeeAccounts::try_from(accounts)?;
+        let instructions = UpdateDynamicFeeInstructions::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instructions,
+        })
+    }
+}
+
+impl<'a> UpdateDynamicFee<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &28;
+
+    pub fn process(&self) -> ProgramResult {
+        let mut config = Config::load_mut(self.accounts.config)?;
+
+        config.check_authority(self.accounts.authority.key())?;
+        config.set_dynamic_fee(
+            self.instructions.enabled,
+            self.instructions.min_fee,
+            self.instructions.max_fee,
+        )?;
+
+        Ok(())
+    }
+}
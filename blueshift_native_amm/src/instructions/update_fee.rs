@@ -0,0 +1,98 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::{
+    instructions::{AccountCheck, SignerAccount},
+    state::Config,
+};
+
+pub struct UpdateFeeAccounts<'a> {
+    pub authority: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for UpdateFeeAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [authority, config] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(authority)?;
+
+        Ok(Self { authority, config })
+    }
+}
+
+pub struct UpdateFeeInstructions {
+    pub fee: u16,
+    // cap on the referrer share of the swap fee, left unchanged when it isn't passed
+    pub max_referral_fee: Option<u16>,
+}
+
+impl<'a> TryFrom<&'a [u8]> for UpdateFeeInstructions {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        // the referral cap is optional, updates of the fee alone can leave it out
+        let max_referral_fee = match data.len() {
+            len if len == size_of::<u16>() => None,
+            len if len == size_of::<u16>() * 2 => Some(u16::from_le_bytes([data[2], data[3]])),
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        let fee = u16::from_le_bytes([data[0], data[1]]);
+
+        if fee > Config::MAX_FEE
+            || max_referral_fee
+                .is_some_and(|max_referral_fee| max_referral_fee > Config::MAX_REFERRAL_FEE)
+        {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self {
+            fee,
+            max_referral_fee,
+        })
+    }
+}
+
+pub struct UpdateFee<'a> {
+    pub accounts: UpdateFeeAccounts<'a>,
+    pub instructions: UpdateFeeInstructions,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for UpdateFee<'a> {
+    type Error = ProgramError;
+
+    fn try_from((accounts, data): (&'a [AccountInfo], &'a [u8])) -> Result<Self, Self::Error> {
+        let accounts = UpdateFeeAccounts::try_from(accounts)?;
+        let instructions = UpdateFeeInstructions::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instructions,
+        })
+    }
+}
+
+impl<'a> UpdateFee<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &8;
+
+    pub fn process(&self) -> ProgramResult {
+        let mut config = Config::load_mut(self.accounts.config)?;
+
+        config.check_authority(self.accounts.authority.key())?;
+
+        // the fee of a canonical pool is fixed, passing it unchanged only updates the referral cap
+        if self.instructions.fee != config.fee() {
+            config.set_fee(self.instructions.fee)?;
+        }
+
+        if let Some(max_referral_fee) = self.instructions.max_referral_fee {
+            config.set_max_referral_fee(max_referral_fee)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::{
+    instructions::{AccountCheck, SignerAccount},
+    state::Config,
+};
+
+pub struct UpdateProtocolFeeAccounts<'a> {
+    pub authority: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for UpdateProtocolFeeAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [authority, config] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(authority)?;
+
+        Ok(Self { authority, config })
+    }
+}
+
+pub struct UpdateProtocolFeeInstructions {
+    pub protocol_fee: u16,
+}
+
+impl<'a> TryFrom<&'a [u8]> for UpdateProtocolFeeInstructions {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<u16>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let protocol_fee = u16::from_le_bytes([data[0], data[1]]);
+
+        if protocol_fee > Config::MAX_PROTOCOL_FEE {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(Self { protocol_fee })
+    }
+}
+
+pub struct UpdateProtocolFee<'a> {
+    pub accounts: UpdateProtocolFeeAccounts<'a>,
+    pub instructions: UpdateProtocolFeeInstructions,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for UpdateProtocolFee<'a> {
+    type Error = ProgramError;
+
+    fn try_from((accounts, data): (&'a [AccountInfo], &'a [u8])) -> Result<Self, Self::Error> {
+        let accounts = UpdateProtocolFeeAccounts::try_from(accounts)?;
+        let instructions = UpdateProtocolFeeInstructions::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instructions,
+        })
+    }
+}
+
+impl<'a> UpdateProtocolFee<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &10;
+
+    pub fn process(&self) -> ProgramResult {
+        let mut config = Config::load_mut(self.accounts.config)?;
+
+        config.check_authority(self.accounts.authority.key())?;
+        config.set_protocol_fee(self.instructions.protocol_fee)?;
+
+        Ok(())
+    }
+}
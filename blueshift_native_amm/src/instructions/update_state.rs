@@ -0,0 +1,89 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::{
+    error::PinocchioError,
+    instructions::{AccountCheck, SignerAccount},
+    state::{AmmState, Config},
+};
+
+pub struct UpdateStateAccounts<'a> {
+    pub authority: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for UpdateStateAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [authority, config] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        SignerAccount::check(authority)?;
+
+        Ok(Self { authority, config })
+    }
+}
+
+pub struct UpdateStateInstructions {
+    pub state: AmmState,
+}
+
+impl<'a> TryFrom<&'a [u8]> for UpdateStateInstructions {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let [state] = data else {
+            return Err(ProgramError::InvalidInstructionData);
+        };
+
+        let state = AmmState::try_from(*state)?;
+
+        // a pool can never be moved back to the uninitialized state, and only a flash swap can
+        // lock it
+        if state == AmmState::Uninitialized || state == AmmState::FlashSwap {
+            return Err(PinocchioError::InvalidPoolState.into());
+        }
+
+        Ok(Self { state })
+    }
+}
+
+pub struct UpdateState<'a> {
+    pub accounts: UpdateStateAccounts<'a>,
+    pub instructions: UpdateStateInstructions,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for UpdateState<'a> {
+    type Error = ProgramError;
+
+    fn try_from((accounts, data): (&'a [AccountInfo], &'a [u8])) -> Result<Self, Self::Error> {
+        let accounts = UpdateStateAccounts::try_from(accounts)?;
+        let instructions = UpdateStateInstructions::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instructions,
+        })
+    }
+}
+
+impl<'a> UpdateState<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &4;
+
+    pub fn process(&self) -> ProgramResult {
+        let mut config = Config::load_mut(self.accounts.config)?;
+
+        // only the pool authority can change the state, and never once it has been renounced
+        config.check_authority(self.accounts.authority.key())?;
+
+        // an open flash swap has to be settled first
+        if config.state() == AmmState::FlashSwap as u8 {
+            return Err(PinocchioError::InvalidPoolState.into());
+        }
+
+        config.set_state(self.instructions.state as u8);
+
+        Ok(())
+    }
+}
@@ -1 +1,266 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::Signer,
+    program_error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
 
+use crate::{
+    curve,
+    error::PinocchioError,
+    instructions::{
+        optional_account, AccountCheck, AssociatedTokenAccount, AssociatedTokenAccountCheck,
+        AssociatedTokenAccountInit, SignerAccount,
+    },
+    state::{AmmState, Config, Oracle},
+    token_interface::{self, Burn, TransferChecked},
+};
+
+pub struct WithdrawAccounts<'a> {
+    pub user: &'a AccountInfo,
+
+    pub mint_x: &'a AccountInfo,
+    pub mint_y: &'a AccountInfo,
+    pub lp_mint: &'a AccountInfo,
+
+    pub config: &'a AccountInfo,
+
+    pub vault_x: &'a AccountInfo,
+    pub vault_y: &'a AccountInfo,
+
+    pub user_x_ata: &'a AccountInfo,
+    pub user_y_ata: &'a AccountInfo,
+    pub user_lp_ata: &'a AccountInfo,
+
+    pub token_program_x: &'a AccountInfo,
+    pub token_program_y: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    pub associated_token_program: &'a AccountInfo,
+
+    pub oracle: Option<&'a AccountInfo>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for WithdrawAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [user, mint_x, mint_y, lp_mint, config, vault_x, vault_y, user_x_ata, user_y_ata, user_lp_ata, token_program_x, token_program_y, token_program, system_program, associated_token_program, remaining @ ..] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // account checks
+        SignerAccount::check(user)?;
+
+        AssociatedTokenAccount::check(user_lp_ata, user, lp_mint)?;
+
+        // the mints and vaults must be the ones the pool was created with
+        let config_data = Config::load(config)?;
+        config_data.check_address(config.key())?;
+
+        if config_data.mint_x() != mint_x.key()
+            || config_data.mint_y() != mint_y.key()
+            || config_data.lp_mint() != lp_mint.key()
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if config_data.mint_x_vault() != vault_x.key()
+            || config_data.mint_y_vault() != vault_y.key()
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        token_interface::check_token_program(token_program_x, mint_x)?;
+        token_interface::check_token_program(token_program_y, mint_y)?;
+        token_interface::check_token_program(token_program, lp_mint)?;
+
+        Ok(Self {
+            user,
+            mint_x,
+            mint_y,
+            lp_mint,
+            config,
+            vault_x,
+            vault_y,
+            user_x_ata,
+            user_y_ata,
+            user_lp_ata,
+            token_program_x,
+            token_program_y,
+            token_program,
+            system_program,
+            associated_token_program,
+            oracle: optional_account(remaining.first()),
+        })
+    }
+}
+
+pub struct WithdrawInstructions {
+    pub amount: u64,
+    pub min_x: u64,
+    pub min_y: u64,
+    pub expiration: i64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for WithdrawInstructions {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<u64>() * 3 + size_of::<i64>() {
+            return Err(ProgramError::InvalidInstructionData);
+        };
+
+        let amount = u64::from_le_bytes(data[0..8].try_into().unwrap());
+        let min_x = u64::from_le_bytes(data[8..16].try_into().unwrap());
+        let min_y = u64::from_le_bytes(data[16..24].try_into().unwrap());
+        let expiration = i64::from_le_bytes(data[24..32].try_into().unwrap());
+
+        if amount == 0 {
+            return Err(PinocchioError::InvalidAmount.into());
+        }
+
+        Ok(Self {
+            amount,
+            min_x,
+            min_y,
+            expiration,
+        })
+    }
+}
+
+pub struct Withdraw<'a> {
+    pub accounts: WithdrawAccounts<'a>,
+    pub instructions: WithdrawInstructions,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for Withdraw<'a> {
+    type Error = ProgramError;
+
+    fn try_from((accounts, data): (&'a [AccountInfo], &'a [u8])) -> Result<Self, Self::Error> {
+        let accounts = WithdrawAccounts::try_from(accounts)?;
+        let instructions = WithdrawInstructions::try_from(data)?;
+
+        AssociatedTokenAccount::init_if_needed(
+            accounts.user_x_ata,
+            accounts.mint_x,
+            accounts.user,
+            accounts.user,
+            accounts.system_program,
+            accounts.token_program_x,
+        )?;
+
+        AssociatedTokenAccount::init_if_needed(
+            accounts.user_y_ata,
+            accounts.mint_y,
+            accounts.user,
+            accounts.user,
+            accounts.system_program,
+            accounts.token_program_y,
+        )?;
+
+        Ok(Self {
+            accounts,
+            instructions,
+        })
+    }
+}
+
+impl<'a> Withdraw<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &3;
+
+    pub fn process(&self) -> ProgramResult {
+        let clock = Clock::get()?;
+
+        if clock.unix_timestamp > self.instructions.expiration {
+            return Err(PinocchioError::Expired.into());
+        }
+
+        let (amount_x, amount_y) = {
+            let mut config = Config::load_mut(self.accounts.config)?;
+
+            // withdrawals stay open while the pool is being wound down
+            config.check_state(&[AmmState::Initialized, AmmState::WithdrawOnly])?;
+
+            let (reserve_x, reserve_y) = config.reserves();
+
+            // the price accumulators only ever see the reserves from before the withdrawal
+            config.update_price_accumulators(reserve_x, reserve_y, &clock);
+
+            if let Some(oracle) = self.accounts.oracle {
+                Oracle::load_mut(oracle)?.record(self.accounts.config.key(), &config)?;
+            }
+
+            let lp_supply =
+                config.lp_supply(token_interface::mint(self.accounts.lp_mint)?.supply())?;
+
+            let (amount_x, amount_y) = curve::withdraw(
+                &config,
+                reserve_x,
+                reserve_y,
+                lp_supply,
+                self.instructions.amount,
+            )?;
+
+            config.withdraw_reserves(amount_x, amount_y)?;
+
+            (amount_x, amount_y)
+        };
+
+        if amount_x == 0 && amount_y == 0 {
+            return Err(PinocchioError::InvalidAmount.into());
+        }
+
+        // the minimums are on what the user receives after any token-2022 transfer fee
+        let received_x = token_interface::transfer_fee(self.accounts.mint_x, clock.epoch)?
+            .amount_received(amount_x)
+            .ok_or(PinocchioError::MathOverflow)?;
+        let received_y = token_interface::transfer_fee(self.accounts.mint_y, clock.epoch)?
+            .amount_received(amount_y)
+            .ok_or(PinocchioError::MathOverflow)?;
+
+        if received_x < self.instructions.min_x || received_y < self.instructions.min_y {
+            return Err(PinocchioError::SlipageExceeded.into());
+        }
+
+        Burn {
+            account: self.accounts.user_lp_ata,
+            mint: self.accounts.lp_mint,
+            authority: self.accounts.user,
+            amount: self.instructions.amount,
+            token_program: self.accounts.token_program.key(),
+        }
+        .invoke()?;
+
+        let config = Config::load(self.accounts.config)?;
+        let config_seeds = config.signer_seeds();
+        let signer = [Signer::from(&config_seeds)];
+
+        TransferChecked {
+            from: self.accounts.vault_x,
+            mint: self.accounts.mint_x,
+            to: self.accounts.user_x_ata,
+            authority: self.accounts.config,
+            amount: amount_x,
+            decimals: token_interface::mint(self.accounts.mint_x)?.decimals(),
+            token_program: self.accounts.token_program_x.key(),
+        }
+        .invoke_signed(&signer)?;
+
+        TransferChecked {
+            from: self.accounts.vault_y,
+            mint: self.accounts.mint_y,
+            to: self.accounts.user_y_ata,
+            authority: self.accounts.config,
+            amount: amount_y,
+            decimals: token_interface::mint(self.accounts.mint_y)?.decimals(),
+            token_program: self.accounts.token_program_y.key(),
+        }
+        .invoke_signed(&signer)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,283 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::Signer,
+    program_error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    curve,
+    error::PinocchioError,
+    instructions::{
+        optional_account, AccountCheck, AssociatedTokenAccount, AssociatedTokenAccountCheck,
+        AssociatedTokenAccountInit, MintInterface, SignerAccount,
+    },
+    state::{AmmState, Config, Oracle},
+    token_interface::{self, MintTo, TransferChecked},
+};
+
+pub struct ZapDepositAccounts<'a> {
+    pub user: &'a AccountInfo,
+
+    pub mint_x: &'a AccountInfo,
+    pub mint_y: &'a AccountInfo,
+    pub lp_mint: &'a AccountInfo,
+
+    pub config: &'a AccountInfo,
+
+    pub vault_x: &'a AccountInfo,
+    pub vault_y: &'a AccountInfo,
+    pub vault_lp: &'a AccountInfo,
+
+    // the user's account of the token being deposited
+    pub user_ata: &'a AccountInfo,
+
+    pub token_program_x: &'a AccountInfo,
+    pub token_program_y: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    pub associated_token_program: &'a AccountInfo,
+
+    pub oracle: Option<&'a AccountInfo>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for ZapDepositAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [user, mint_x, mint_y, lp_mint, config, vault_x, vault_y, user_ata, vault_lp, token_program_x, token_program_y, token_program, system_program, associated_token_program, remaining @ ..] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // account checks
+        SignerAccount::check(user)?;
+        MintInterface::check(mint_x)?;
+        MintInterface::check(mint_y)?;
+
+        token_interface::check_token_program(token_program_x, mint_x)?;
+        token_interface::check_token_program(token_program_y, mint_y)?;
+        token_interface::check_token_program(token_program, lp_mint)?;
+
+        // the mints, vaults and lp mint must be the ones the pool was created with
+        let config_data = Config::load(config)?;
+        config_data.check_address(config.key())?;
+
+        if config_data.mint_x() != mint_x.key()
+            || config_data.mint_y() != mint_y.key()
+            || config_data.lp_mint() != lp_mint.key()
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if config_data.mint_x_vault() != vault_x.key()
+            || config_data.mint_y_vault() != vault_y.key()
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(Self {
+            user,
+            mint_x,
+            mint_y,
+            lp_mint,
+            config,
+            vault_x,
+            vault_y,
+            vault_lp,
+            user_ata,
+            token_program_x,
+            token_program_y,
+            token_program,
+            system_program,
+            associated_token_program,
+            oracle: optional_account(remaining.first()),
+        })
+    }
+}
+
+pub struct ZapDepositInstructions {
+    pub is_x: bool,
+    pub amount: u64,
+    pub min_lp_amount: u64,
+    pub expiration: i64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for ZapDepositInstructions {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<u8>() + size_of::<u64>() * 2 + size_of::<i64>() {
+            return Err(ProgramError::InvalidInstructionData);
+        };
+
+        let is_x = match data[0] {
+            0 => false,
+            1 => true,
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+        let amount = u64::from_le_bytes(data[1..9].try_into().unwrap());
+        let min_lp_amount = u64::from_le_bytes(data[9..17].try_into().unwrap());
+        let expiration = i64::from_le_bytes(data[17..25].try_into().unwrap());
+
+        if amount == 0 {
+            return Err(PinocchioError::InvalidMintAmount.into());
+        }
+
+        Ok(Self {
+            is_x,
+            amount,
+            min_lp_amount,
+            expiration,
+        })
+    }
+}
+
+// deposits only x or only y. the pool swaps the part that balances the deposit internally, so
+// the swapped tokens never leave the vaults, and mints lp tokens for the whole amount
+pub struct ZapDeposit<'a> {
+    pub accounts: ZapDepositAccounts<'a>,
+    pub instructions: ZapDepositInstructions,
+}
+
+impl<'a> TryFrom<(&'a [AccountInfo], &'a [u8])> for ZapDeposit<'a> {
+    type Error = ProgramError;
+
+    fn try_from((accounts, data): (&'a [AccountInfo], &'a [u8])) -> Result<Self, Self::Error> {
+        let accounts = ZapDepositAccounts::try_from(accounts)?;
+        let instructions = ZapDepositInstructions::try_from(data)?;
+
+        let mint_in = match instructions.is_x {
+            true => accounts.mint_x,
+            false => accounts.mint_y,
+        };
+
+        AssociatedTokenAccount::check(accounts.user_ata, accounts.user, mint_in)?;
+
+        // the vaults are created with the pool, only the user's lp account may be missing
+        AssociatedTokenAccount::init_if_needed(
+            accounts.vault_lp,
+            accounts.lp_mint,
+            accounts.user,
+            accounts.user,
+            accounts.system_program,
+            accounts.token_program,
+        )?;
+
+        Ok(Self {
+            accounts,
+            instructions,
+        })
+    }
+}
+
+impl<'a> ZapDeposit<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &25;
+
+    pub fn process(&self) -> ProgramResult {
+        let clock = Clock::get()?;
+
+        if clock.unix_timestamp > self.instructions.expiration {
+            return Err(PinocchioError::Expired.into());
+        }
+
+        let (mint_in, vault_in, token_program_in) = match self.instructions.is_x {
+            true => (
+                self.accounts.mint_x,
+                self.accounts.vault_x,
+                self.accounts.token_program_x,
+            ),
+            false => (
+                self.accounts.mint_y,
+                self.accounts.vault_y,
+                self.accounts.token_program_y,
+            ),
+        };
+
+        // token-2022 transfer fees are withheld from what arrives in the vault, the lp tokens
+        // are only minted for what the pool actually receives
+        let received = token_interface::transfer_fee(mint_in, clock.epoch)?
+            .amount_received(self.instructions.amount)
+            .ok_or(PinocchioError::MathOverflow)?;
+
+        let lp_amount = {
+            let mut config = Config::load_mut(self.accounts.config)?;
+
+            // deposits are only allowed while the pool is fully active
+            config.check_state(&[AmmState::Initialized])?;
+
+            let (reserve_x, reserve_y) = config.reserves();
+
+            // the price accumulators only ever see the reserves from before the deposit
+            config.update_price_accumulators(reserve_x, reserve_y, &clock);
+
+            if let Some(oracle) = self.accounts.oracle {
+                Oracle::load_mut(oracle)?.record(self.accounts.config.key(), &config)?;
+            }
+
+            let lp_supply =
+                config.lp_supply(token_interface::mint(self.accounts.lp_mint)?.supply())?;
+
+            let quote = curve::zap_deposit(
+                &config,
+                reserve_x,
+                reserve_y,
+                lp_supply,
+                self.instructions.is_x,
+                received,
+                clock.unix_timestamp,
+            )?;
+
+            // all of the deposit stays in the pool, the swapped part included
+            match self.instructions.is_x {
+                true => config.deposit_reserves(received, 0)?,
+                false => config.deposit_reserves(0, received)?,
+            }
+
+            // the internal swap or the imbalance pays the protocol its share like any swap
+            config.accrue_deposit_fees(quote.fee_x, quote.fee_y)?;
+
+            // the internal swap moves the price like any other swap
+            let new_reserves = config.reserves();
+            config.update_volatility((reserve_x, reserve_y), new_reserves, clock.unix_timestamp);
+
+            quote.lp_amount
+        };
+
+        if lp_amount == 0 {
+            return Err(PinocchioError::InvalidAmount.into());
+        }
+
+        if lp_amount < self.instructions.min_lp_amount {
+            return Err(PinocchioError::SlipageExceeded.into());
+        }
+
+        TransferChecked {
+            from: self.accounts.user_ata,
+            mint: mint_in,
+            to: vault_in,
+            amount: self.instructions.amount,
+            authority: self.accounts.user,
+            decimals: token_interface::mint(mint_in)?.decimals(),
+            token_program: token_program_in.key(),
+        }
+        .invoke()?;
+
+        let config = Config::load(self.accounts.config)?;
+        let config_seeds = config.signer_seeds();
+        let signer = [Signer::from(&config_seeds)];
+
+        MintTo {
+            account: self.accounts.vault_lp,
+            mint: self.accounts.lp_mint,
+            amount: lp_amount,
+            mint_authority: self.accounts.config,
+            token_program: self.accounts.token_program.key(),
+        }
+        .invoke_signed(&signer)?;
+
+        Ok(())
+    }
+}
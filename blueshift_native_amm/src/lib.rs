@@ -1,5 +1,6 @@
 #![no_std]
 #![allow(unexpected_cfgs)]
+#![allow(clippy::missing_safety_doc, clippy::too_many_arguments)]
 
 use pinocchio::pubkey::Pubkey;
 
@@ -7,8 +8,12 @@ use pinocchio::pubkey::Pubkey;
 mod entrypoint;
 
 pub mod instructions;
+pub mod seeds;
 pub mod state;
 pub mod error;
+pub mod curve;
+pub mod math;
+pub mod token_interface;
 
 pub const ID: Pubkey = [
     0x0f, 0x1e, 0x6b, 0x14, 0x21, 0xc0, 0x4a, 0x07, 0x04, 0x31, 0x26, 0x5c, 0x19, 0xc5, 0xbb, 0xee,
@@ -0,0 +1,393 @@
+// u128 only fits the product of two u64, the curve math needs a bit more headroom than that.
+// these helpers keep the full 256 bit product around while dividing it back down.
+
+const LOW_MASK: u128 = u64::MAX as u128;
+
+// full 256 bit product of two u128 as (high, low)
+#[inline(always)]
+pub fn full_mul(a: u128, b: u128) -> (u128, u128) {
+    let (a_hi, a_lo) = (a >> 64, a & LOW_MASK);
+    let (b_hi, b_lo) = (b >> 64, b & LOW_MASK);
+
+    let lo_lo = a_lo * b_lo;
+    let lo_hi = a_lo * b_hi;
+    let hi_lo = a_hi * b_lo;
+    let hi_hi = a_hi * b_hi;
+
+    // at most three 64 bit values, can't overflow
+    let middle = (lo_lo >> 64) + (lo_hi & LOW_MASK) + (hi_lo & LOW_MASK);
+
+    let low = (lo_lo & LOW_MASK) | (middle << 64);
+    let high = hi_hi + (lo_hi >> 64) + (hi_lo >> 64) + (middle >> 64);
+
+    (high, low)
+}
+
+// (high, low) / divisor as (quotient, remainder), None if the quotient doesn't fit in a u128
+fn div_rem_256(high: u128, low: u128, divisor: u128) -> Option<(u128, u128)> {
+    if divisor == 0 || high >= divisor {
+        return None;
+    }
+
+    if high == 0 {
+        return Some((low / divisor, low % divisor));
+    }
+
+    // plain long division, the remainder is always smaller than the divisor
+    let mut quotient = 0u128;
+    let mut remainder = high;
+
+    for i in (0..128).rev() {
+        let carry = remainder >> 127;
+        remainder = (remainder << 1) | ((low >> i) & 1);
+        quotient <<= 1;
+
+        if carry == 1 || remainder >= divisor {
+            remainder = remainder.wrapping_sub(divisor);
+            quotient |= 1;
+        }
+    }
+
+    Some((quotient, remainder))
+}
+
+// a * b / c rounded down
+#[inline(always)]
+pub fn mul_div(a: u128, b: u128, c: u128) -> Option<u128> {
+    let (high, low) = full_mul(a, b);
+    div_rem_256(high, low, c).map(|(quotient, _)| quotient)
+}
+
+// a * b / c rounded up
+#[inline(always)]
+pub fn mul_div_ceil(a: u128, b: u128, c: u128) -> Option<u128> {
+    let (high, low) = full_mul(a, b);
+    let (quotient, remainder) = div_rem_256(high, low, c)?;
+
+    match remainder {
+        0 => Some(quotient),
+        _ => quotient.checked_add(1),
+    }
+}
+
+// square root of a * b rounded down, newton's method on the 256 bit product starting from a
+// power of two above the root
+pub fn sqrt_mul(a: u128, b: u128) -> Option<u128> {
+    let (high, low) = full_mul(a, b);
+
+    let bits = match high {
+        0 => 128 - low.leading_zeros(),
+        _ => 256 - high.leading_zeros(),
+    };
+
+    if bits == 0 {
+        return Some(0);
+    }
+
+    let mut root = match bits.div_ceil(2) {
+        128 => u128::MAX,
+        shift => 1 << shift,
+    };
+
+    loop {
+        // (root + quotient) / 2 without overflowing
+        let (quotient, _) = div_rem_256(high, low, root)?;
+        let next = (root >> 1) + (quotient >> 1) + (root & quotient & 1);
+
+        if next >= root {
+            return Some(root);
+        }
+
+        root = next;
+    }
+}
+
+// 1.0 in q64.64 fixed point
+pub const ONE_Q64: u128 = 1 << 64;
+
+// 2^(-2^-(i + 1)) in q64.64, rounded down
+const EXP2_NEG_FRACTIONS: [u128; 64] = [
+    0xb504f333f9de6484,
+    0xd744fccad69d6af4,
+    0xeac0c6e7dd24392e,
+    0xf5257d152486cc2c,
+    0xfa83b2db722a033a,
+    0xfd3e0c0cf486c174,
+    0xfe9e115c7b8f884b,
+    0xff4ecb59511ec8a5,
+    0xffa756521c8daed1,
+    0xffd3a751c0f7e10b,
+    0xffe9d2b2f7db2755,
+    0xfff4e91bff1b8c3d,
+    0xfffa747ea0040664,
+    0xfffd3a3b7814eb53,
+    0xfffe9d1cc60ddab1,
+    0xffff4e8e25879bfa,
+    0xffffa7470363f451,
+    0xffffd3a37dda0313,
+    0xffffe9d1bdf703ae,
+    0xfffff4e8debe025e,
+    0xfffffa746f4fa150,
+    0xfffffd3a37a3f8b0,
+    0xfffffe9d1bd1065a,
+    0xffffff4e8de845ad,
+    0xffffffa746f41376,
+    0xffffffd3a37a05e3,
+    0xffffffe9d1bd01fb,
+    0xfffffff4e8de80c0,
+    0xfffffffa746f4050,
+    0xfffffffd3a37a024,
+    0xfffffffe9d1bd011,
+    0xffffffff4e8de808,
+    0xffffffffa746f404,
+    0xffffffffd3a37a02,
+    0xffffffffe9d1bd01,
+    0xfffffffff4e8de80,
+    0xfffffffffa746f40,
+    0xfffffffffd3a37a0,
+    0xfffffffffe9d1bd0,
+    0xffffffffff4e8de8,
+    0xffffffffffa746f4,
+    0xffffffffffd3a37a,
+    0xffffffffffe9d1bd,
+    0xfffffffffff4e8de,
+    0xfffffffffffa746f,
+    0xfffffffffffd3a37,
+    0xfffffffffffe9d1b,
+    0xffffffffffff4e8d,
+    0xffffffffffffa746,
+    0xffffffffffffd3a3,
+    0xffffffffffffe9d1,
+    0xfffffffffffff4e8,
+    0xfffffffffffffa74,
+    0xfffffffffffffd3a,
+    0xfffffffffffffe9d,
+    0xffffffffffffff4e,
+    0xffffffffffffffa7,
+    0xffffffffffffffd3,
+    0xffffffffffffffe9,
+    0xfffffffffffffff4,
+    0xfffffffffffffffa,
+    0xfffffffffffffffd,
+    0xfffffffffffffffe,
+    0xffffffffffffffff,
+];
+
+// a * b for two q64.64 numbers, rounded down
+#[inline(always)]
+pub fn mul_q64(a: u128, b: u128) -> Option<u128> {
+    let (high, low) = full_mul(a, b);
+
+    if high >> 64 != 0 {
+        return None;
+    }
+
+    Some((high << 64) | (low >> 64))
+}
+
+// log2 of a q64.64 number that is at least one, rounded down
+pub fn log2_q64(x: u128) -> Option<u128> {
+    if x < ONE_Q64 {
+        return None;
+    }
+
+    // integer part from the position of the highest bit
+    let integer = 127 - x.leading_zeros() - 64;
+    let mut result = (integer as u128) << 64;
+    let mut y = x >> integer;
+
+    // fractional bits one at a time, squaring y doubles its log
+    for i in 1..=64 {
+        y = mul_q64(y, y)?;
+
+        if y >= 2 * ONE_Q64 {
+            y >>= 1;
+            result |= 1 << (64 - i);
+        }
+    }
+
+    Some(result)
+}
+
+// 2^-x for a q64.64 number, rounded down
+pub fn exp2_neg_q64(x: u128) -> u128 {
+    let integer = x >> 64;
+
+    if integer >= 64 {
+        return 0;
+    }
+
+    let fraction = x & LOW_MASK;
+    let mut result = ONE_Q64;
+
+    for (i, factor) in EXP2_NEG_FRACTIONS.iter().enumerate() {
+        if fraction & (1 << (63 - i)) != 0 {
+            // both factors are below one so this can't overflow
+            result = (result * (*factor)) >> 64;
+        }
+    }
+
+    result >> integer
+}
+
+// base^exponent for q64.64 numbers, through 2^(exponent * log2(base)). the result is only
+// accurate to about 2^-56 relative, callers have to round it in the pool's favour
+pub fn pow_q64(base: u128, exponent: u128) -> Option<u128> {
+    if base == 0 {
+        return Some(0);
+    }
+
+    if base >= ONE_Q64 {
+        let power = mul_q64(log2_q64(base)?, exponent)?;
+
+        let integer = power >> 64;
+        let fraction = power & LOW_MASK;
+
+        // the integer part has to fit in the upper 64 bits
+        if integer >= 63 {
+            return None;
+        }
+
+        if fraction == 0 {
+            return Some(ONE_Q64 << integer);
+        }
+
+        // 2^(i + f) = 2^(i + 1) * 2^-(1 - f)
+        return Some(exp2_neg_q64(ONE_Q64 - fraction) << (integer + 1));
+    }
+
+    // 2^(exponent * log2(base)) = 2^-(exponent * log2(1 / base))
+    let inverse = mul_div(ONE_Q64, ONE_Q64, base)?;
+    let power = mul_q64(log2_q64(inverse)?, exponent)?;
+
+    Some(exp2_neg_q64(power))
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+
+    const ONE: f64 = ONE_Q64 as f64;
+
+    // q64.64 values with at most 53 significant bits, so the float reference sees them exactly
+    fn exact_q64(value: f64) -> u128 {
+        let q64 = (value * ONE) as u128;
+        let dropped = (128 - q64.leading_zeros()).saturating_sub(53);
+
+        q64 >> dropped << dropped
+    }
+
+    #[test]
+    fn full_mul_keeps_the_high_bits() {
+        assert_eq!(full_mul(1 << 64, 1 << 64), (1, 0));
+        assert_eq!(full_mul(u128::MAX, u128::MAX), (u128::MAX - 1, 1));
+        assert_eq!(full_mul(u128::MAX, 2), (1, u128::MAX - 1));
+    }
+
+    #[test]
+    fn mul_div_rounding() {
+        assert_eq!(mul_div(7, 3, 2), Some(10));
+        assert_eq!(mul_div_ceil(7, 3, 2), Some(11));
+        assert_eq!(mul_div_ceil(8, 3, 2), Some(12));
+
+        // the 256 bit intermediate is divided back down exactly
+        assert_eq!(mul_div(u128::MAX, u128::MAX, u128::MAX), Some(u128::MAX));
+        assert_eq!(mul_div(u128::MAX, 1 << 100, 1 << 101), Some(u128::MAX >> 1));
+        assert_eq!(mul_div_ceil(u128::MAX, 1 << 100, 1 << 101), Some(1 << 127));
+    }
+
+    #[test]
+    fn mul_div_overflow() {
+        assert_eq!(mul_div(1, 1, 0), None);
+        assert_eq!(mul_div_ceil(1, 1, 0), None);
+
+        // quotients above u128::MAX
+        assert_eq!(mul_div(u128::MAX, 2, 1), None);
+        assert_eq!(mul_div(u128::MAX, u128::MAX, u128::MAX - 1), None);
+
+        // 7 * b = 2^129 - 1, half of it is u128::MAX and a half so only rounding up overflows
+        let b = 97_223_533_405_982_418_132_392_744_980_505_203_273;
+        assert_eq!(full_mul(7, b), (1, u128::MAX));
+        assert_eq!(mul_div(7, b, 2), Some(u128::MAX));
+        assert_eq!(mul_div_ceil(7, b, 2), None);
+    }
+
+    #[test]
+    fn div_rem_256_matches_u128_division() {
+        for (a, b, c) in [
+            (12_345_678_901_234_567_890, 9_876_543_210, 1_000_000_007),
+            (u64::MAX as u128, u64::MAX as u128, 3),
+            (1 << 100, 1 << 27, (1 << 100) + 1),
+        ] {
+            let (high, low) = full_mul(a, b);
+            assert_eq!(high, 0);
+            assert_eq!(div_rem_256(high, low, c), Some((a * b / c, a * b % c)));
+        }
+    }
+
+    #[test]
+    fn log2_q64_rounds_down() {
+        for x in [1.0, 1.000_000_1, 1.5, 2.0, 3.0, 1e10, 1.8e19] {
+            let q64 = exact_q64(x);
+            let exact = (q64 as f64 / ONE).log2();
+            let log = log2_q64(q64).unwrap() as f64 / ONE;
+
+            assert!(log <= exact + 1e-15, "log2 {x}");
+            assert!(log >= exact - 1e-13, "log2 {x}");
+        }
+
+        assert_eq!(log2_q64(ONE_Q64), Some(0));
+        assert_eq!(log2_q64(ONE_Q64 << 63), Some(63 << 64));
+        assert_eq!(log2_q64(ONE_Q64 - 1), None);
+    }
+
+    #[test]
+    fn exp2_neg_q64_rounds_down() {
+        for x in [1e-9, 0.5, 0.999, 10.5, 40.25, 63.9] {
+            let q64 = exact_q64(x);
+            let exact = 2f64.powf(-(q64 as f64 / ONE)) * ONE;
+            let power = exp2_neg_q64(q64) as f64;
+
+            assert!(power <= exact * (1.0 + 1e-15), "2^-{x}");
+            assert!(power >= exact * (1.0 - 1e-15) - 1.0, "2^-{x}");
+        }
+
+        assert_eq!(exp2_neg_q64(0), ONE_Q64);
+        assert_eq!(exp2_neg_q64(ONE_Q64), ONE_Q64 / 2);
+        assert_eq!(exp2_neg_q64(63 << 64), 2);
+        assert_eq!(exp2_neg_q64(64 << 64), 0);
+    }
+
+    #[test]
+    fn pow_q64_at_extreme_weights() {
+        // the weight ratios of a 1/99 and a 99/1 pool and everything in between, against bases
+        // from pools drained down to a trillionth up to ones grown by a trillion
+        let exponents = [100.0 / 9_900.0, 0.25, 0.5, 1.0, 2.0, 4.0, 9_900.0 / 100.0];
+        let bases = [
+            1e-12, 1e-6, 0.001, 0.5, 0.999_999, 1.000_001, 1.5, 1e3, 1e6, 1e12,
+        ];
+
+        for exponent in exponents.map(exact_q64) {
+            for base in bases.map(exact_q64) {
+                let exact = (base as f64 / ONE).powf(exponent as f64 / ONE) * ONE;
+
+                // results past the q64.64 range are refused rather than wrapped
+                let Some(power) = pow_q64(base, exponent) else {
+                    assert!(exact >= 2f64.powi(126), "{base} ^ {exponent}");
+                    continue;
+                };
+
+                // off by at most one unit plus 2^-56 relative, what the curves round away
+                let error = (power as f64 - exact).abs();
+                assert!(error <= exact / 2f64.powi(56) + 1.0, "{base} ^ {exponent}");
+            }
+        }
+
+        assert_eq!(pow_q64(0, ONE_Q64), Some(0));
+        assert_eq!(pow_q64(ONE_Q64, 99 * ONE_Q64), Some(ONE_Q64));
+        assert_eq!(pow_q64(ONE_Q64 << 2, ONE_Q64 / 2), Some(ONE_Q64 << 1));
+        assert_eq!(pow_q64(ONE_Q64 << 40, 99 * ONE_Q64), None);
+    }
+}
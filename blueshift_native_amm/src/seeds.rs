@@ -0,0 +1,243 @@
+use pinocchio::{instruction::Seed, pubkey::Pubkey};
+
+// every pda in the program is derived from the seeds below, off-chain clients should build
+// their addresses from these functions so they always match what the program expects
+
+pub const CONFIG: &[u8] = b"config";
+pub const LP_MINT: &[u8] = b"lp_mint";
+pub const ORACLE: &[u8] = b"oracle";
+pub const CONCENTRATED_POOL: &[u8] = b"concentrated_pool";
+pub const TICK_ARRAY: &[u8] = b"tick_array";
+pub const POSITION: &[u8] = b"position";
+pub const FACTORY: &[u8] = b"factory";
+pub const POOL: &[u8] = b"pool";
+pub const ORDER: &[u8] = b"order";
+
+// pool config: [b"config", seed, mint_x, mint_y]
+// the user supplied seed allows more than one pool for the same pair
+#[inline(always)]
+pub fn config<'a>(seed: &'a [u8; 8], mint_x: &'a Pubkey, mint_y: &'a Pubkey) -> [&'a [u8]; 4] {
+    [CONFIG, seed, mint_x, mint_y]
+}
+
+#[inline(always)]
+pub fn config_with_bump<'a>(
+    seed: &'a [u8; 8],
+    mint_x: &'a Pubkey,
+    mint_y: &'a Pubkey,
+    bump: &'a [u8; 1],
+) -> [&'a [u8]; 5] {
+    [CONFIG, seed, mint_x, mint_y, bump]
+}
+
+#[inline(always)]
+pub fn config_signer<'a>(
+    seed: &'a [u8; 8],
+    mint_x: &'a Pubkey,
+    mint_y: &'a Pubkey,
+    bump: &'a [u8; 1],
+) -> [Seed<'a>; 5] {
+    [
+        Seed::from(CONFIG),
+        Seed::from(seed),
+        Seed::from(mint_x),
+        Seed::from(mint_y),
+        Seed::from(bump),
+    ]
+}
+
+// canonical pool config: [b"pool", mint_x, mint_y, fee_tier as u16 le bytes]
+// mint_x has to sort before mint_y, so there is exactly one pool per pair and fee tier
+#[inline(always)]
+pub fn pool<'a>(mint_x: &'a Pubkey, mint_y: &'a Pubkey, fee_tier: &'a [u8; 2]) -> [&'a [u8]; 4] {
+    [POOL, mint_x, mint_y, fee_tier]
+}
+
+#[inline(always)]
+pub fn pool_with_bump<'a>(
+    mint_x: &'a Pubkey,
+    mint_y: &'a Pubkey,
+    fee_tier: &'a [u8; 2],
+    bump: &'a [u8; 1],
+) -> [&'a [u8]; 5] {
+    [POOL, mint_x, mint_y, fee_tier, bump]
+}
+
+#[inline(always)]
+pub fn pool_signer<'a>(
+    mint_x: &'a Pubkey,
+    mint_y: &'a Pubkey,
+    fee_tier: &'a [u8; 2],
+    bump: &'a [u8; 1],
+) -> [Seed<'a>; 5] {
+    [
+        Seed::from(POOL),
+        Seed::from(mint_x),
+        Seed::from(mint_y),
+        Seed::from(fee_tier),
+        Seed::from(bump),
+    ]
+}
+
+// factory: [b"factory"], one per program
+#[inline(always)]
+pub fn factory() -> [&'static [u8]; 1] {
+    [FACTORY]
+}
+
+#[inline(always)]
+pub fn factory_signer(bump: &[u8; 1]) -> [Seed<'_>; 2] {
+    [Seed::from(FACTORY), Seed::from(bump)]
+}
+
+// lp mint: [b"lp_mint", config]
+#[inline(always)]
+pub fn lp_mint(config: &Pubkey) -> [&[u8]; 2] {
+    [LP_MINT, config]
+}
+
+#[inline(always)]
+pub fn lp_mint_with_bump<'a>(config: &'a Pubkey, bump: &'a [u8; 1]) -> [&'a [u8]; 3] {
+    [LP_MINT, config, bump]
+}
+
+#[inline(always)]
+pub fn lp_mint_signer<'a>(config: &'a Pubkey, bump: &'a [u8; 1]) -> [Seed<'a>; 3] {
+    [Seed::from(LP_MINT), Seed::from(config), Seed::from(bump)]
+}
+
+// vaults are the associated token accounts of the config, derived under the ata program:
+// [config, token_program, mint]
+#[inline(always)]
+pub fn vault<'a>(config: &'a Pubkey, token_program: &'a Pubkey, mint: &'a Pubkey) -> [&'a [u8]; 3] {
+    [config, token_program, mint]
+}
+
+// twap oracle: [b"oracle", config]
+#[inline(always)]
+pub fn oracle(config: &Pubkey) -> [&[u8]; 2] {
+    [ORACLE, config]
+}
+
+#[inline(always)]
+pub fn oracle_signer<'a>(config: &'a Pubkey, bump: &'a [u8; 1]) -> [Seed<'a>; 3] {
+    [Seed::from(ORACLE), Seed::from(config), Seed::from(bump)]
+}
+
+// concentrated liquidity pool: [b"concentrated_pool", seed, mint_x, mint_y]
+#[inline(always)]
+pub fn concentrated_pool<'a>(
+    seed: &'a [u8; 8],
+    mint_x: &'a Pubkey,
+    mint_y: &'a Pubkey,
+) -> [&'a [u8]; 4] {
+    [CONCENTRATED_POOL, seed, mint_x, mint_y]
+}
+
+#[inline(always)]
+pub fn concentrated_pool_with_bump<'a>(
+    seed: &'a [u8; 8],
+    mint_x: &'a Pubkey,
+    mint_y: &'a Pubkey,
+    bump: &'a [u8; 1],
+) -> [&'a [u8]; 5] {
+    [CONCENTRATED_POOL, seed, mint_x, mint_y, bump]
+}
+
+#[inline(always)]
+pub fn concentrated_pool_signer<'a>(
+    seed: &'a [u8; 8],
+    mint_x: &'a Pubkey,
+    mint_y: &'a Pubkey,
+    bump: &'a [u8; 1],
+) -> [Seed<'a>; 5] {
+    [
+        Seed::from(CONCENTRATED_POOL),
+        Seed::from(seed),
+        Seed::from(mint_x),
+        Seed::from(mint_y),
+        Seed::from(bump),
+    ]
+}
+
+// tick array: [b"tick_array", pool, start_tick_index as i32 le bytes]
+#[inline(always)]
+pub fn tick_array<'a>(pool: &'a Pubkey, start_tick_index: &'a [u8; 4]) -> [&'a [u8]; 3] {
+    [TICK_ARRAY, pool, start_tick_index]
+}
+
+#[inline(always)]
+pub fn tick_array_signer<'a>(
+    pool: &'a Pubkey,
+    start_tick_index: &'a [u8; 4],
+    bump: &'a [u8; 1],
+) -> [Seed<'a>; 4] {
+    [
+        Seed::from(TICK_ARRAY),
+        Seed::from(pool),
+        Seed::from(start_tick_index),
+        Seed::from(bump),
+    ]
+}
+
+// position: [b"position", pool, owner, tick_lower, tick_upper], ticks as i32 le bytes
+#[inline(always)]
+pub fn position<'a>(
+    pool: &'a Pubkey,
+    owner: &'a Pubkey,
+    tick_lower: &'a [u8; 4],
+    tick_upper: &'a [u8; 4],
+) -> [&'a [u8]; 5] {
+    [POSITION, pool, owner, tick_lower, tick_upper]
+}
+
+#[inline(always)]
+pub fn position_signer<'a>(
+    pool: &'a Pubkey,
+    owner: &'a Pubkey,
+    tick_lower: &'a [u8; 4],
+    tick_upper: &'a [u8; 4],
+    bump: &'a [u8; 1],
+) -> [Seed<'a>; 6] {
+    [
+        Seed::from(POSITION),
+        Seed::from(pool),
+        Seed::from(owner),
+        Seed::from(tick_lower),
+        Seed::from(tick_upper),
+        Seed::from(bump),
+    ]
+}
+
+// limit order: [b"order", config, owner, seed]
+// the owner supplied seed allows more than one open order per pool
+#[inline(always)]
+pub fn order<'a>(config: &'a Pubkey, owner: &'a Pubkey, seed: &'a [u8; 8]) -> [&'a [u8]; 4] {
+    [ORDER, config, owner, seed]
+}
+
+#[inline(always)]
+pub fn order_with_bump<'a>(
+    config: &'a Pubkey,
+    owner: &'a Pubkey,
+    seed: &'a [u8; 8],
+    bump: &'a [u8; 1],
+) -> [&'a [u8]; 5] {
+    [ORDER, config, owner, seed, bump]
+}
+
+#[inline(always)]
+pub fn order_signer<'a>(
+    config: &'a Pubkey,
+    owner: &'a Pubkey,
+    seed: &'a [u8; 8],
+    bump: &'a [u8; 1],
+) -> [Seed<'a>; 5] {
+    [
+        Seed::from(ORDER),
+        Seed::from(config),
+        Seed::from(owner),
+        Seed::from(seed),
+        Seed::from(bump),
+    ]
+}
@@ -0,0 +1,321 @@
+use pinocchio::{
+    account_info::{AccountInfo, Ref, RefMut},
+    instruction::Seed,
+    program_error::ProgramError,
+    pubkey::{create_program_address, Pubkey},
+};
+
+use crate::{error::PinocchioError, seeds, state::AmmState};
+
+// concentrated liquidity pool, the liquidity itself lives in the positions and tick arrays.
+// only the liquidity of the positions around the current price is tracked here
+#[repr(C)]
+pub struct ConcentratedPool {
+    seed: [u8; 8],
+    // can pause the pool and collect the protocol fees
+    authority: Pubkey,
+    mint_x: Pubkey,
+    mint_y: Pubkey,
+    vault_x: Pubkey,
+    vault_y: Pubkey,
+    fee: u16,
+    tick_spacing: u16,
+    bump: [u8; 1],
+    liquidity: [u8; 16],
+    sqrt_price: [u8; 16],
+    tick_current: [u8; 4],
+    // fees earned per unit of liquidity since the pool was created, q64.64
+    fee_growth_global_x: [u8; 16],
+    fee_growth_global_y: [u8; 16],
+    state: u8,
+    // share of the swap fee kept by the protocol, in basis points of the fee
+    protocol_fee: [u8; 2],
+    protocol_fees_x: [u8; 8],
+    protocol_fees_y: [u8; 8],
+}
+
+impl ConcentratedPool {
+    pub const LEN: usize = size_of::<Self>();
+
+    // wider spacings make swaps cheaper but positions coarser
+    pub const MAX_TICK_SPACING: u16 = 16_384;
+
+    #[inline(always)]
+    pub fn load(account_info: &AccountInfo) -> Result<Ref<'_, Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if !account_info.is_owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        Ok(Ref::map(account_info.try_borrow_data()?, |data| unsafe {
+            Self::from_bytes_unchecked(data)
+        }))
+    }
+
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked(bytes: &[u8]) -> &Self {
+        &*(bytes.as_ptr() as *const ConcentratedPool)
+    }
+
+    #[inline(always)]
+    pub fn load_mut(account_info: &AccountInfo) -> Result<RefMut<'_, Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if !account_info.is_owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        Ok(RefMut::map(
+            account_info.try_borrow_mut_data()?,
+            |data| unsafe { Self::from_bytes_unchecked_mut(data) },
+        ))
+    }
+
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked_mut(bytes: &mut [u8]) -> &mut Self {
+        &mut *(bytes.as_mut_ptr() as *mut ConcentratedPool)
+    }
+
+    pub fn set_inner(
+        &mut self,
+        seed: u64,
+        authority: Pubkey,
+        mint_x: Pubkey,
+        mint_y: Pubkey,
+        vault_x: Pubkey,
+        vault_y: Pubkey,
+        fee: u16,
+        tick_spacing: u16,
+        bump: u8,
+        sqrt_price: u128,
+        tick_current: i32,
+        protocol_fee: u16,
+    ) {
+        self.seed = seed.to_le_bytes();
+        self.authority = authority;
+        self.mint_x = mint_x;
+        self.mint_y = mint_y;
+        self.vault_x = vault_x;
+        self.vault_y = vault_y;
+        self.fee = fee;
+        self.tick_spacing = tick_spacing;
+        self.bump = [bump];
+        self.liquidity = [0; 16];
+        self.sqrt_price = sqrt_price.to_le_bytes();
+        self.tick_current = tick_current.to_le_bytes();
+        self.fee_growth_global_x = [0; 16];
+        self.fee_growth_global_y = [0; 16];
+        self.state = AmmState::Initialized as u8;
+        self.protocol_fee = protocol_fee.to_le_bytes();
+        self.protocol_fees_x = [0; 8];
+        self.protocol_fees_y = [0; 8];
+    }
+
+    #[inline(always)]
+    pub fn seed(&self) -> u64 {
+        u64::from_le_bytes(self.seed)
+    }
+
+    #[inline(always)]
+    pub fn authority(&self) -> &Pubkey {
+        &self.authority
+    }
+
+    #[inline(always)]
+    pub fn mint_x(&self) -> &Pubkey {
+        &self.mint_x
+    }
+
+    #[inline(always)]
+    pub fn mint_y(&self) -> &Pubkey {
+        &self.mint_y
+    }
+
+    #[inline(always)]
+    pub fn vault_x(&self) -> &Pubkey {
+        &self.vault_x
+    }
+
+    #[inline(always)]
+    pub fn vault_y(&self) -> &Pubkey {
+        &self.vault_y
+    }
+
+    #[inline(always)]
+    pub fn fee(&self) -> u16 {
+        self.fee
+    }
+
+    #[inline(always)]
+    pub fn tick_spacing(&self) -> u16 {
+        self.tick_spacing
+    }
+
+    #[inline(always)]
+    pub fn bump(&self) -> u8 {
+        self.bump[0]
+    }
+
+    #[inline(always)]
+    pub fn liquidity(&self) -> u128 {
+        u128::from_le_bytes(self.liquidity)
+    }
+
+    #[inline(always)]
+    pub fn sqrt_price(&self) -> u128 {
+        u128::from_le_bytes(self.sqrt_price)
+    }
+
+    #[inline(always)]
+    pub fn tick_current(&self) -> i32 {
+        i32::from_le_bytes(self.tick_current)
+    }
+
+    #[inline(always)]
+    pub fn fee_growth_global_x(&self) -> u128 {
+        u128::from_le_bytes(self.fee_growth_global_x)
+    }
+
+    #[inline(always)]
+    pub fn fee_growth_global_y(&self) -> u128 {
+        u128::from_le_bytes(self.fee_growth_global_y)
+    }
+
+    #[inline(always)]
+    pub fn state(&self) -> u8 {
+        self.state
+    }
+
+    #[inline(always)]
+    pub fn protocol_fee(&self) -> u16 {
+        u16::from_le_bytes(self.protocol_fee)
+    }
+
+    #[inline(always)]
+    pub fn protocol_fees_x(&self) -> u64 {
+        u64::from_le_bytes(self.protocol_fees_x)
+    }
+
+    #[inline(always)]
+    pub fn protocol_fees_y(&self) -> u64 {
+        u64::from_le_bytes(self.protocol_fees_y)
+    }
+
+    #[inline(always)]
+    pub fn set_state(&mut self, state: u8) {
+        self.state = state;
+    }
+
+    #[inline(always)]
+    pub fn set_liquidity(&mut self, liquidity: u128) {
+        self.liquidity = liquidity.to_le_bytes();
+    }
+
+    // price, tick and fee growth move together at the end of a swap
+    pub fn set_swap_state(
+        &mut self,
+        sqrt_price: u128,
+        tick_current: i32,
+        liquidity: u128,
+        fee_growth_global_x: u128,
+        fee_growth_global_y: u128,
+    ) {
+        self.sqrt_price = sqrt_price.to_le_bytes();
+        self.tick_current = tick_current.to_le_bytes();
+        self.liquidity = liquidity.to_le_bytes();
+        self.fee_growth_global_x = fee_growth_global_x.to_le_bytes();
+        self.fee_growth_global_y = fee_growth_global_y.to_le_bytes();
+    }
+
+    // adds a position's liquidity change if the position covers the current price
+    pub fn apply_liquidity_delta(
+        &mut self,
+        tick_lower: i32,
+        tick_upper: i32,
+        liquidity_delta: i128,
+    ) -> Result<(), ProgramError> {
+        let tick_current = self.tick_current();
+
+        if tick_current < tick_lower || tick_current >= tick_upper {
+            return Ok(());
+        }
+
+        let liquidity = self
+            .liquidity()
+            .checked_add_signed(liquidity_delta)
+            .ok_or(PinocchioError::MathOverflow)?;
+
+        self.set_liquidity(liquidity);
+        Ok(())
+    }
+
+    // protocol cut of a swap fee, rounded down like the pool's own
+    #[inline(always)]
+    pub fn protocol_fee_amount(&self, swap_fee: u64) -> Result<u64, ProgramError> {
+        Ok((swap_fee as u128)
+            .checked_mul(self.protocol_fee() as u128)
+            .ok_or(PinocchioError::MathOverflow)?
+            .checked_div(10_000)
+            .ok_or(PinocchioError::MathOverflow)? as u64)
+    }
+
+    #[inline(always)]
+    pub fn accrue_protocol_fees(&mut self, is_x: bool, amount: u64) -> Result<(), ProgramError> {
+        let accrued = match is_x {
+            true => &mut self.protocol_fees_x,
+            false => &mut self.protocol_fees_y,
+        };
+
+        *accrued = u64::from_le_bytes(*accrued)
+            .checked_add(amount)
+            .ok_or(PinocchioError::MathOverflow)?
+            .to_le_bytes();
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn clear_protocol_fees(&mut self) {
+        self.protocol_fees_x = [0; 8];
+        self.protocol_fees_y = [0; 8];
+    }
+
+    #[inline(always)]
+    pub fn check_state(&self, allowed: &[AmmState]) -> Result<(), ProgramError> {
+        if !allowed.iter().any(|state| *state as u8 == self.state) {
+            return Err(PinocchioError::InvalidPoolState.into());
+        }
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn check_authority(&self, authority: &Pubkey) -> Result<(), ProgramError> {
+        if self.authority.ne(authority) {
+            return Err(PinocchioError::InvalidAuthority.into());
+        }
+        Ok(())
+    }
+
+    // the pool pda signs for the vaults
+    #[inline(always)]
+    pub fn signer_seeds(&self) -> [Seed<'_>; 5] {
+        seeds::concentrated_pool_signer(&self.seed, &self.mint_x, &self.mint_y, &self.bump)
+    }
+
+    #[inline(always)]
+    pub fn check_address(&self, pool: &Pubkey) -> Result<(), ProgramError> {
+        let expected = create_program_address(
+            &seeds::concentrated_pool_with_bump(&self.seed, &self.mint_x, &self.mint_y, &self.bump),
+            &crate::ID,
+        )?;
+
+        if expected.ne(pool) {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,1272 @@
+use pinocchio::{
+    account_info::{AccountInfo, Ref, RefMut},
+    instruction::Seed,
+    program_error::ProgramError,
+    pubkey::{create_program_address, Pubkey},
+    sysvars::clock::Clock,
+};
+
+use crate::{curve, error::PinocchioError, math::mul_div, seeds};
+
+#[repr(C)]
+pub struct Config {
+    seed: [u8; 8],
+    authority: Pubkey,
+    pending_authority: Pubkey,
+    mint_x: Pubkey,
+    mint_y: Pubkey,
+    mint_x_vault: Pubkey,
+    mint_y_vault: Pubkey,
+    lp_mint: Pubkey,
+    fee: u16,
+    protocol_fee: u16,
+    bump: [u8; 1],
+    lp_mint_bump: [u8; 1],
+    state: u8,
+    // protocol fees still sitting in the vaults, they are not part of the lp reserves
+    protocol_fees_x: u64,
+    protocol_fees_y: u64,
+    // lp reserves, only moved by the instructions that move tokens in and out of the vaults so
+    // tokens sent to the vaults directly can't skew the price
+    reserve_x: u64,
+    reserve_y: u64,
+    // twap accumulators, sum of the q64.64 spot price times the seconds it was active
+    price_x_cumulative: [u8; 16],
+    price_y_cumulative: [u8; 16],
+    last_update_timestamp: i64,
+    last_update_slot: u64,
+    curve_type: u8,
+    // stableswap amplification, moves linearly from the initial to the target value during a ramp
+    initial_amp: u64,
+    target_amp: u64,
+    ramp_start_timestamp: i64,
+    ramp_stop_timestamp: i64,
+    // weighted pool weights in basis points, zero for the other curves
+    weight_x: u16,
+    weight_y: u16,
+    // amounts sent out by the open flash swap, only set while the pool is in the flash swap state
+    flash_amount_x: u64,
+    flash_amount_y: u64,
+    // lp tokens locked by the first deposit. they are never minted but count towards the supply,
+    // so donating to a pool with a tiny supply can't inflate the share price
+    locked_liquidity: u64,
+    // dynamic fee mode, the swap fee rises with recent price moves and is kept between the
+    // bounds. the volatility is in basis points of price movement since the reference price
+    dynamic_fee: u8,
+    min_fee: u16,
+    max_fee: u16,
+    volatility_accumulator: u64,
+    volatility_reference: u64,
+    volatility_reference_price: [u8; 16],
+    last_volatility_update: i64,
+    // fee tier of a pool created through the factory, it lives at the canonical pool address
+    // instead of the seeded one. zero for pools created with a custom seed
+    fee_tier: u16,
+    // highest referrer share swaps can ask for, in basis points of the fee. set by the authority
+    // and never above `MAX_REFERRAL_FEE`
+    max_referral_fee: u16,
+}
+
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq)]
+pub enum AmmState {
+    Uninitialized = 0u8,
+    Initialized = 1u8,
+    Disabled = 2u8,
+    WithdrawOnly = 3u8,
+    // tokens were lent out by a flash swap and the pool is locked until it is settled
+    FlashSwap = 4u8,
+}
+
+impl TryFrom<u8> for AmmState {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(AmmState::Uninitialized),
+            1 => Ok(AmmState::Initialized),
+            2 => Ok(AmmState::Disabled),
+            3 => Ok(AmmState::WithdrawOnly),
+            4 => Ok(AmmState::FlashSwap),
+            _ => Err(ProgramError::InvalidInstructionData),
+        }
+    }
+}
+
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq)]
+pub enum CurveType {
+    ConstantProduct = 0u8,
+    Stable = 1u8,
+    Weighted = 2u8,
+}
+
+impl TryFrom<u8> for CurveType {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(CurveType::ConstantProduct),
+            1 => Ok(CurveType::Stable),
+            2 => Ok(CurveType::Weighted),
+            _ => Err(ProgramError::InvalidInstructionData),
+        }
+    }
+}
+
+impl Config {
+    pub const LEN: usize = size_of::<Self>();
+
+    // upper bound for the swap fee in basis points
+    pub const MAX_FEE: u16 = 1000;
+
+    // upper bound for the protocol share of the swap fee, in basis points of the fee
+    pub const MAX_PROTOCOL_FEE: u16 = 5000;
+
+    // upper bound for the referrer share of the swap fee, in basis points of the fee. together
+    // with the protocol share it always leaves part of the fee to the lps
+    pub const MAX_REFERRAL_FEE: u16 = 2000;
+
+    // bounds for the stableswap amplification coefficient
+    pub const MIN_AMP: u64 = 1;
+    pub const MAX_AMP: u64 = 1_000_000;
+
+    // a single ramp can at most multiply or divide the amplification by this much
+    pub const MAX_AMP_CHANGE: u64 = 10;
+
+    // ramps have to be spread over at least a day so lps can react
+    pub const MIN_RAMP_DURATION: i64 = 86_400;
+
+    // weighted pool weights add up to this, neither side can go below the minimum
+    pub const TOTAL_WEIGHT: u16 = 10_000;
+    pub const MIN_WEIGHT: u16 = 100;
+
+    // dynamic fee: swaps closer together than the filter period keep measuring from the same
+    // reference, after it the volatility is scaled down by the reduction factor (basis points)
+    // and after the decay period it is dropped
+    pub const VOLATILITY_FILTER_PERIOD: i64 = 30;
+    pub const VOLATILITY_DECAY_PERIOD: i64 = 600;
+    pub const VOLATILITY_REDUCTION_FACTOR: u64 = 5_000;
+
+    // fee added on top of the base fee per basis point of volatility, in basis points of it
+    pub const VARIABLE_FEE_CONTROL: u64 = 5_000;
+
+    // price moves beyond this many basis points don't raise the fee any further
+    pub const MAX_VOLATILITY: u64 = 100_000;
+
+    // inline always attribute rather than adding the function call to the cll stack
+    // it adds the function code to the call stack which eliminate the overhead function call
+    #[inline(always)]
+    pub fn load(account_info: &AccountInfo) -> Result<Ref<'_, Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        Ok(Ref::map(account_info.try_borrow_data()?, |data| unsafe {
+            Self::from_bytes_unchecked(data)
+        }))
+    }
+
+    #[inline(always)]
+    pub unsafe fn load_unchecked(account_info: &AccountInfo) -> Result<&Self, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        Ok(Self::from_bytes_unchecked(
+            account_info.borrow_data_unchecked(),
+        ))
+    }
+
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked(bytes: &[u8]) -> &Self {
+        &*(bytes.as_ptr() as *const Config)
+    }
+
+    #[inline(always)]
+    pub fn load_mut(account_info: &AccountInfo) -> Result<RefMut<'_, Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        Ok(RefMut::map(
+            account_info.try_borrow_mut_data()?,
+            |data| unsafe { Self::from_bytes_unchecked_mut(data) },
+        ))
+    }
+
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked_mut(bytes: &mut [u8]) -> &mut Self {
+        &mut *(bytes.as_mut_ptr() as *mut Config)
+    }
+
+    pub fn set_inner(
+        &mut self,
+        seed: u64,
+        authority: Pubkey,
+        mint_x: Pubkey,
+        mint_y: Pubkey,
+        mint_x_vault: Pubkey,
+        mint_y_vault: Pubkey,
+        lp_mint: Pubkey,
+        fee: u16,
+        protocol_fee: u16,
+        bump: u8,
+        lp_mint_bump: u8,
+        state: u8,
+        curve_type: CurveType,
+        amp: u64,
+        weight_x: u16,
+        weight_y: u16,
+    ) -> Result<(), ProgramError> {
+        self.seed = seed.to_le_bytes();
+        self.authority = authority;
+        self.pending_authority = Pubkey::default();
+        self.mint_x = mint_x;
+        self.mint_y = mint_y;
+        self.mint_x_vault = mint_x_vault;
+        self.mint_y_vault = mint_y_vault;
+        self.lp_mint = lp_mint;
+        self.fee = fee;
+        self.protocol_fee = protocol_fee;
+        self.bump = [bump];
+        self.lp_mint_bump = [lp_mint_bump];
+        self.state = state;
+        self.protocol_fees_x = 0;
+        self.protocol_fees_y = 0;
+        self.reserve_x = 0;
+        self.reserve_y = 0;
+        self.price_x_cumulative = [0; 16];
+        self.price_y_cumulative = [0; 16];
+        self.last_update_timestamp = 0;
+        self.last_update_slot = 0;
+        self.curve_type = curve_type as u8;
+        self.initial_amp = amp;
+        self.target_amp = amp;
+        self.ramp_start_timestamp = 0;
+        self.ramp_stop_timestamp = 0;
+        self.weight_x = weight_x;
+        self.weight_y = weight_y;
+        self.flash_amount_x = 0;
+        self.flash_amount_y = 0;
+        self.locked_liquidity = 0;
+        self.dynamic_fee = 0;
+        self.min_fee = 0;
+        self.max_fee = 0;
+        self.volatility_accumulator = 0;
+        self.volatility_reference = 0;
+        self.volatility_reference_price = [0; 16];
+        self.last_volatility_update = 0;
+        self.fee_tier = 0;
+        self.max_referral_fee = Self::MAX_REFERRAL_FEE;
+        Ok(())
+    }
+
+    // marks the pool as the canonical one for its pair and `fee_tier`, which also changes the
+    // seeds it signs with
+    #[inline(always)]
+    pub fn set_fee_tier(&mut self, fee_tier: u16) {
+        self.fee_tier = fee_tier;
+    }
+
+    #[inline(always)]
+    pub fn seed(&self) -> u64 {
+        u64::from_le_bytes(self.seed)
+    }
+
+    #[inline(always)]
+    pub fn authority(&self) -> &Pubkey {
+        &self.authority
+    }
+
+    #[inline(always)]
+    pub fn pending_authority(&self) -> &Pubkey {
+        &self.pending_authority
+    }
+
+    #[inline(always)]
+    pub fn mint_x(&self) -> &Pubkey {
+        &self.mint_x
+    }
+
+    #[inline(always)]
+    pub fn mint_y(&self) -> &Pubkey {
+        &self.mint_y
+    }
+
+    #[inline(always)]
+    pub fn mint_x_vault(&self) -> &Pubkey {
+        &self.mint_x_vault
+    }
+
+    #[inline(always)]
+    pub fn mint_y_vault(&self) -> &Pubkey {
+        &self.mint_y_vault
+    }
+
+    #[inline(always)]
+    pub fn lp_mint(&self) -> &Pubkey {
+        &self.lp_mint
+    }
+
+    #[inline(always)]
+    pub fn fee(&self) -> u16 {
+        self.fee
+    }
+
+    #[inline(always)]
+    pub fn protocol_fee(&self) -> u16 {
+        self.protocol_fee
+    }
+
+    #[inline(always)]
+    pub fn protocol_fees_x(&self) -> u64 {
+        self.protocol_fees_x
+    }
+
+    #[inline(always)]
+    pub fn protocol_fees_y(&self) -> u64 {
+        self.protocol_fees_y
+    }
+
+    #[inline(always)]
+    pub fn price_x_cumulative(&self) -> u128 {
+        u128::from_le_bytes(self.price_x_cumulative)
+    }
+
+    #[inline(always)]
+    pub fn price_y_cumulative(&self) -> u128 {
+        u128::from_le_bytes(self.price_y_cumulative)
+    }
+
+    #[inline(always)]
+    pub fn last_update_timestamp(&self) -> i64 {
+        self.last_update_timestamp
+    }
+
+    #[inline(always)]
+    pub fn last_update_slot(&self) -> u64 {
+        self.last_update_slot
+    }
+
+    #[inline(always)]
+    pub fn curve_type(&self) -> Result<CurveType, ProgramError> {
+        CurveType::try_from(self.curve_type)
+    }
+
+    #[inline(always)]
+    pub fn initial_amp(&self) -> u64 {
+        self.initial_amp
+    }
+
+    #[inline(always)]
+    pub fn target_amp(&self) -> u64 {
+        self.target_amp
+    }
+
+    #[inline(always)]
+    pub fn ramp_start_timestamp(&self) -> i64 {
+        self.ramp_start_timestamp
+    }
+
+    #[inline(always)]
+    pub fn ramp_stop_timestamp(&self) -> i64 {
+        self.ramp_stop_timestamp
+    }
+
+    #[inline(always)]
+    pub fn weight_x(&self) -> u16 {
+        self.weight_x
+    }
+
+    #[inline(always)]
+    pub fn weight_y(&self) -> u16 {
+        self.weight_y
+    }
+
+    // amounts sent out by the open flash swap
+    #[inline(always)]
+    pub fn flash_amounts(&self) -> (u64, u64) {
+        (self.flash_amount_x, self.flash_amount_y)
+    }
+
+    #[inline(always)]
+    pub fn locked_liquidity(&self) -> u64 {
+        self.locked_liquidity
+    }
+
+    #[inline(always)]
+    pub fn dynamic_fee(&self) -> bool {
+        self.dynamic_fee == 1
+    }
+
+    #[inline(always)]
+    pub fn min_fee(&self) -> u16 {
+        self.min_fee
+    }
+
+    #[inline(always)]
+    pub fn max_fee(&self) -> u16 {
+        self.max_fee
+    }
+
+    #[inline(always)]
+    pub fn volatility_accumulator(&self) -> u64 {
+        self.volatility_accumulator
+    }
+
+    #[inline(always)]
+    pub fn volatility_reference(&self) -> u64 {
+        self.volatility_reference
+    }
+
+    #[inline(always)]
+    pub fn volatility_reference_price(&self) -> u128 {
+        u128::from_le_bytes(self.volatility_reference_price)
+    }
+
+    #[inline(always)]
+    pub fn last_volatility_update(&self) -> i64 {
+        self.last_volatility_update
+    }
+
+    #[inline(always)]
+    pub fn fee_tier(&self) -> u16 {
+        self.fee_tier
+    }
+
+    #[inline(always)]
+    pub fn max_referral_fee(&self) -> u16 {
+        self.max_referral_fee
+    }
+
+    // the supply lp shares are priced against, the minted tokens plus the locked liquidity
+    #[inline(always)]
+    pub fn lp_supply(&self, minted_supply: u64) -> Result<u64, ProgramError> {
+        minted_supply
+            .checked_add(self.locked_liquidity)
+            .ok_or(PinocchioError::MathOverflow.into())
+    }
+
+    #[inline(always)]
+    pub fn bump(&self) -> u8 {
+        self.bump[0]
+    }
+
+    #[inline(always)]
+    pub fn lp_mint_bump(&self) -> u8 {
+        self.lp_mint_bump[0]
+    }
+
+    // the fee tier as it appears in the canonical pool seeds. the account is laid out little
+    // endian, so these are the `to_le_bytes` the address was derived from
+    #[inline(always)]
+    fn fee_tier_bytes(&self) -> &[u8; 2] {
+        unsafe { &*(&self.fee_tier as *const u16 as *const [u8; 2]) }
+    }
+
+    // seeds used by the config pda to sign for the vaults and the lp mint
+    #[inline(always)]
+    pub fn signer_seeds(&self) -> [Seed<'_>; 5] {
+        match self.fee_tier() {
+            0 => seeds::config_signer(&self.seed, &self.mint_x, &self.mint_y, &self.bump),
+            _ => seeds::pool_signer(
+                &self.mint_x,
+                &self.mint_y,
+                self.fee_tier_bytes(),
+                &self.bump,
+            ),
+        }
+    }
+
+    // re-derives the config pda from the stored bump, cheaper than searching for the bump again
+    #[inline(always)]
+    pub fn check_address(&self, config: &Pubkey) -> Result<(), ProgramError> {
+        let seeds = match self.fee_tier() {
+            0 => seeds::config_with_bump(&self.seed, &self.mint_x, &self.mint_y, &self.bump),
+            _ => seeds::pool_with_bump(
+                &self.mint_x,
+                &self.mint_y,
+                self.fee_tier_bytes(),
+                &self.bump,
+            ),
+        };
+
+        let expected = create_program_address(&seeds, &crate::ID)?;
+
+        if expected.ne(config) {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn state(&self) -> u8 {
+        self.state
+    }
+
+    #[inline(always)]
+    pub fn set_state(&mut self, state: u8) {
+        self.state = state;
+    }
+
+    #[inline(always)]
+    pub fn set_authority(&mut self, authority: Pubkey) {
+        self.authority = authority;
+    }
+
+    #[inline(always)]
+    pub fn set_pending_authority(&mut self, pending_authority: Pubkey) {
+        self.pending_authority = pending_authority;
+    }
+
+    #[inline(always)]
+    pub fn set_fee(&mut self, fee: u16) -> Result<(), ProgramError> {
+        // a canonical pool charges its tier, integrators rely on it
+        if self.fee_tier() != 0 {
+            return Err(PinocchioError::InvalidFeeTier.into());
+        }
+
+        if fee > Self::MAX_FEE {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        self.fee = fee;
+        Ok(())
+    }
+
+    // turns the dynamic fee on or off. the base fee doesn't have to lie between the bounds, the
+    // fee charged is always clamped to them
+    pub fn set_dynamic_fee(
+        &mut self,
+        enabled: bool,
+        min_fee: u16,
+        max_fee: u16,
+    ) -> Result<(), ProgramError> {
+        if enabled && (min_fee > max_fee || max_fee > Self::MAX_FEE) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        if enabled && self.fee_tier() != 0 {
+            return Err(PinocchioError::InvalidFeeTier.into());
+        }
+
+        // volatility measured before the mode was switched doesn't carry over
+        if enabled != self.dynamic_fee() {
+            self.volatility_accumulator = 0;
+            self.volatility_reference = 0;
+            self.volatility_reference_price = [0; 16];
+            self.last_volatility_update = 0;
+        }
+
+        self.dynamic_fee = enabled as u8;
+        self.min_fee = min_fee;
+        self.max_fee = max_fee;
+        Ok(())
+    }
+
+    // volatility the fee is computed from at `timestamp`, decayed the same way the next swap
+    // resets its reference
+    pub fn volatility_at(&self, timestamp: i64) -> u64 {
+        let elapsed = timestamp.saturating_sub(self.last_volatility_update);
+
+        if elapsed < Self::VOLATILITY_FILTER_PERIOD {
+            self.volatility_accumulator
+        } else if elapsed < Self::VOLATILITY_DECAY_PERIOD {
+            self.volatility_accumulator * Self::VOLATILITY_REDUCTION_FACTOR / 10_000
+        } else {
+            0
+        }
+    }
+
+    // fee in basis points charged by swaps and unbalanced deposits at `timestamp`. it only
+    // depends on the config and the time, so quotes can reproduce it off-chain
+    pub fn swap_fee(&self, timestamp: i64) -> u16 {
+        if !self.dynamic_fee() {
+            return self.fee;
+        }
+
+        let variable_fee = self.volatility_at(timestamp) * Self::VARIABLE_FEE_CONTROL / 10_000;
+
+        (self.fee as u64)
+            .saturating_add(variable_fee)
+            .clamp(self.min_fee as u64, self.max_fee as u64) as u16
+    }
+
+    // measures how far a swap moved the price, has to be called with the reserves from before
+    // and after it. once the filter period has passed since the last swap the reference price
+    // moves to the current one and the volatility so far is decayed into the reference
+    pub fn update_volatility(
+        &mut self,
+        (old_reserve_x, old_reserve_y): (u64, u64),
+        (new_reserve_x, new_reserve_y): (u64, u64),
+        timestamp: i64,
+    ) {
+        if !self.dynamic_fee() {
+            return;
+        }
+
+        if timestamp.saturating_sub(self.last_volatility_update) >= Self::VOLATILITY_FILTER_PERIOD {
+            self.volatility_reference = self.volatility_at(timestamp);
+
+            if let Some((price, _)) =
+                curve::spot_prices(self, old_reserve_x, old_reserve_y, timestamp)
+            {
+                self.volatility_reference_price = price.to_le_bytes();
+            }
+        }
+
+        let reference_price = self.volatility_reference_price();
+
+        let price_move = curve::spot_prices(self, new_reserve_x, new_reserve_y, timestamp)
+            .filter(|_| reference_price > 0)
+            .and_then(|(price, _)| {
+                mul_div(price.abs_diff(reference_price), 10_000, reference_price)
+            })
+            .map_or(0, |price_move| {
+                price_move.min(Self::MAX_VOLATILITY as u128) as u64
+            });
+
+        self.volatility_accumulator = self
+            .volatility_reference
+            .saturating_add(price_move)
+            .min(Self::MAX_VOLATILITY);
+        self.last_volatility_update = timestamp;
+    }
+
+    #[inline(always)]
+    pub fn set_protocol_fee(&mut self, protocol_fee: u16) -> Result<(), ProgramError> {
+        if protocol_fee > Self::MAX_PROTOCOL_FEE {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        self.protocol_fee = protocol_fee;
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn set_max_referral_fee(&mut self, max_referral_fee: u16) -> Result<(), ProgramError> {
+        if max_referral_fee > Self::MAX_REFERRAL_FEE {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        self.max_referral_fee = max_referral_fee;
+        Ok(())
+    }
+
+    // protocol cut of a swap fee, rounded down so the lps never lose to rounding
+    #[inline(always)]
+    pub fn protocol_fee_amount(&self, swap_fee: u64) -> Result<u64, ProgramError> {
+        Ok((swap_fee as u128)
+            .checked_mul(self.protocol_fee as u128)
+            .ok_or(PinocchioError::MathOverflow)?
+            .checked_div(10_000)
+            .ok_or(PinocchioError::MathOverflow)? as u64)
+    }
+
+    // referrer cut of a swap fee, rounded down like the protocol cut
+    #[inline(always)]
+    pub fn referral_fee_amount(
+        &self,
+        swap_fee: u64,
+        referral_fee: u16,
+    ) -> Result<u64, ProgramError> {
+        Ok((swap_fee as u128)
+            .checked_mul(referral_fee as u128)
+            .ok_or(PinocchioError::MathOverflow)?
+            .checked_div(10_000)
+            .ok_or(PinocchioError::MathOverflow)? as u64)
+    }
+
+    #[inline(always)]
+    pub fn accrue_protocol_fees(&mut self, is_x: bool, amount: u64) -> Result<(), ProgramError> {
+        let accrued = match is_x {
+            true => &mut self.protocol_fees_x,
+            false => &mut self.protocol_fees_y,
+        };
+
+        *accrued = accrued
+            .checked_add(amount)
+            .ok_or(PinocchioError::MathOverflow)?;
+        Ok(())
+    }
+
+    // the protocol's part of the fees a deposit paid, moved out of the reserves once the deposit
+    // has been added to them
+    pub fn accrue_deposit_fees(&mut self, fee_x: u64, fee_y: u64) -> Result<(), ProgramError> {
+        let protocol_fee_x = self.protocol_fee_amount(fee_x)?;
+        let protocol_fee_y = self.protocol_fee_amount(fee_y)?;
+
+        self.reserve_x = self
+            .reserve_x
+            .checked_sub(protocol_fee_x)
+            .ok_or(PinocchioError::MathOverflow)?;
+        self.reserve_y = self
+            .reserve_y
+            .checked_sub(protocol_fee_y)
+            .ok_or(PinocchioError::MathOverflow)?;
+
+        self.accrue_protocol_fees(true, protocol_fee_x)?;
+        self.accrue_protocol_fees(false, protocol_fee_y)
+    }
+
+    #[inline(always)]
+    pub fn clear_protocol_fees(&mut self) {
+        self.protocol_fees_x = 0;
+        self.protocol_fees_y = 0;
+    }
+
+    #[inline(always)]
+    pub fn reserves(&self) -> (u64, u64) {
+        (self.reserve_x, self.reserve_y)
+    }
+
+    #[inline(always)]
+    pub fn set_reserves(&mut self, reserve_x: u64, reserve_y: u64) {
+        self.reserve_x = reserve_x;
+        self.reserve_y = reserve_y;
+    }
+
+    pub fn deposit_reserves(&mut self, amount_x: u64, amount_y: u64) -> Result<(), ProgramError> {
+        self.reserve_x = self
+            .reserve_x
+            .checked_add(amount_x)
+            .ok_or(PinocchioError::MathOverflow)?;
+        self.reserve_y = self
+            .reserve_y
+            .checked_add(amount_y)
+            .ok_or(PinocchioError::MathOverflow)?;
+        Ok(())
+    }
+
+    pub fn withdraw_reserves(&mut self, amount_x: u64, amount_y: u64) -> Result<(), ProgramError> {
+        self.reserve_x = self
+            .reserve_x
+            .checked_sub(amount_x)
+            .ok_or(PinocchioError::MathOverflow)?;
+        self.reserve_y = self
+            .reserve_y
+            .checked_sub(amount_y)
+            .ok_or(PinocchioError::MathOverflow)?;
+        Ok(())
+    }
+
+    // `amount_in` is what the lps keep of the input, without the protocol fee
+    pub fn swap_reserves(
+        &mut self,
+        is_x: bool,
+        amount_in: u64,
+        amount_out: u64,
+    ) -> Result<(), ProgramError> {
+        match is_x {
+            true => {
+                self.deposit_reserves(amount_in, 0)?;
+                self.withdraw_reserves(0, amount_out)
+            }
+            false => {
+                self.deposit_reserves(0, amount_in)?;
+                self.withdraw_reserves(amount_out, 0)
+            }
+        }
+    }
+
+    // what the vault balances hold for the lps, anything above the reserves was sent in directly
+    #[inline(always)]
+    pub fn vault_reserves(
+        &self,
+        vault_x_amount: u64,
+        vault_y_amount: u64,
+    ) -> Result<(u64, u64), ProgramError> {
+        let reserve_x = vault_x_amount
+            .checked_sub(self.protocol_fees_x)
+            .ok_or(PinocchioError::MathOverflow)?;
+        let reserve_y = vault_y_amount
+            .checked_sub(self.protocol_fees_y)
+            .ok_or(PinocchioError::MathOverflow)?;
+
+        Ok((reserve_x, reserve_y))
+    }
+
+    // accumulators carried forward to `timestamp` with the given reserves, without storing them.
+    // they wrap on overflow like uniswap v2, consumers only ever look at the difference
+    pub fn cumulative_prices_at(
+        &self,
+        reserve_x: u64,
+        reserve_y: u64,
+        timestamp: i64,
+    ) -> (u128, u128) {
+        let mut price_x_cumulative = self.price_x_cumulative();
+        let mut price_y_cumulative = self.price_y_cumulative();
+
+        let elapsed = timestamp.saturating_sub(self.last_update_timestamp);
+
+        if elapsed > 0 {
+            if let Some((price_x, price_y)) =
+                curve::spot_prices(self, reserve_x, reserve_y, timestamp)
+            {
+                price_x_cumulative =
+                    price_x_cumulative.wrapping_add(price_x.wrapping_mul(elapsed as u128));
+                price_y_cumulative =
+                    price_y_cumulative.wrapping_add(price_y.wrapping_mul(elapsed as u128));
+            }
+        }
+
+        (price_x_cumulative, price_y_cumulative)
+    }
+
+    // has to be called with the reserves from before the instruction moves any tokens
+    pub fn update_price_accumulators(&mut self, reserve_x: u64, reserve_y: u64, clock: &Clock) {
+        if clock.unix_timestamp <= self.last_update_timestamp {
+            return;
+        }
+
+        let (price_x_cumulative, price_y_cumulative) =
+            self.cumulative_prices_at(reserve_x, reserve_y, clock.unix_timestamp);
+
+        self.price_x_cumulative = price_x_cumulative.to_le_bytes();
+        self.price_y_cumulative = price_y_cumulative.to_le_bytes();
+        self.last_update_timestamp = clock.unix_timestamp;
+        self.last_update_slot = clock.slot;
+    }
+
+    // amplification in effect at `timestamp`, interpolated while a ramp is running
+    pub fn amp(&self, timestamp: i64) -> u64 {
+        if timestamp >= self.ramp_stop_timestamp {
+            return self.target_amp;
+        }
+
+        if timestamp <= self.ramp_start_timestamp {
+            return self.initial_amp;
+        }
+
+        let elapsed = (timestamp - self.ramp_start_timestamp) as u128;
+        let duration = (self.ramp_stop_timestamp - self.ramp_start_timestamp) as u128;
+
+        let (initial, target) = (self.initial_amp as u128, self.target_amp as u128);
+
+        let amp = match target >= initial {
+            true => initial + (target - initial) * elapsed / duration,
+            false => initial - (initial - target) * elapsed / duration,
+        };
+
+        amp as u64
+    }
+
+    // starts moving the amplification towards `target_amp`, reaching it at `stop_timestamp`
+    pub fn ramp_amp(
+        &mut self,
+        target_amp: u64,
+        timestamp: i64,
+        stop_timestamp: i64,
+    ) -> Result<(), ProgramError> {
+        if self.curve_type()? != CurveType::Stable {
+            return Err(PinocchioError::InvalidCurve.into());
+        }
+
+        // a running ramp has to finish or be stopped first
+        if timestamp < self.ramp_stop_timestamp {
+            return Err(PinocchioError::InvalidAmp.into());
+        }
+
+        if stop_timestamp < timestamp.saturating_add(Self::MIN_RAMP_DURATION) {
+            return Err(PinocchioError::InvalidAmp.into());
+        }
+
+        if !(Self::MIN_AMP..=Self::MAX_AMP).contains(&target_amp) {
+            return Err(PinocchioError::InvalidAmp.into());
+        }
+
+        let current_amp = self.amp(timestamp);
+
+        if target_amp > current_amp.saturating_mul(Self::MAX_AMP_CHANGE)
+            || current_amp > target_amp.saturating_mul(Self::MAX_AMP_CHANGE)
+        {
+            return Err(PinocchioError::InvalidAmp.into());
+        }
+
+        self.initial_amp = current_amp;
+        self.target_amp = target_amp;
+        self.ramp_start_timestamp = timestamp;
+        self.ramp_stop_timestamp = stop_timestamp;
+        Ok(())
+    }
+
+    // freezes the amplification at its current value
+    pub fn stop_ramp_amp(&mut self, timestamp: i64) -> Result<(), ProgramError> {
+        if self.curve_type()? != CurveType::Stable {
+            return Err(PinocchioError::InvalidCurve.into());
+        }
+
+        let current_amp = self.amp(timestamp);
+
+        self.initial_amp = current_amp;
+        self.target_amp = current_amp;
+        self.ramp_start_timestamp = timestamp;
+        self.ramp_stop_timestamp = timestamp;
+        Ok(())
+    }
+
+    // checks the pool is in one of the states an instruction is allowed to run in
+    #[inline(always)]
+    pub fn check_state(&self, allowed: &[AmmState]) -> Result<(), ProgramError> {
+        if !allowed.iter().any(|state| *state as u8 == self.state) {
+            return Err(PinocchioError::InvalidPoolState.into());
+        }
+        Ok(())
+    }
+
+    // locks the pool until the flash swap is settled, every other instruction that checks the
+    // state is rejected in the meantime so the reserves stay as they were before it
+    pub fn open_flash_swap(&mut self, amount_x: u64, amount_y: u64) -> Result<(), ProgramError> {
+        self.check_state(&[AmmState::Initialized])?;
+
+        self.state = AmmState::FlashSwap as u8;
+        self.flash_amount_x = amount_x;
+        self.flash_amount_y = amount_y;
+        Ok(())
+    }
+
+    pub fn close_flash_swap(&mut self) -> Result<(), ProgramError> {
+        self.check_state(&[AmmState::FlashSwap])?;
+
+        self.state = AmmState::Initialized as u8;
+        self.flash_amount_x = 0;
+        self.flash_amount_y = 0;
+        Ok(())
+    }
+
+    // locks the first `curve::MINIMUM_LIQUIDITY` lp tokens of the first deposit, returns what is
+    // left for the depositor
+    pub fn lock_minimum_liquidity(&mut self, lp_amount: u64) -> Result<u64, ProgramError> {
+        let remaining = lp_amount
+            .checked_sub(curve::MINIMUM_LIQUIDITY)
+            .filter(|remaining| *remaining > 0)
+            .ok_or(PinocchioError::InvalidMintSupply)?;
+
+        self.locked_liquidity = curve::MINIMUM_LIQUIDITY;
+        Ok(remaining)
+    }
+
+    pub fn has_authority(&self) -> Option<Pubkey> {
+        if self.authority != Pubkey::default() {
+            Some(self.authority)
+        } else {
+            None
+        }
+    }
+
+    // checks the signer is the pool authority, a renounced pool has no authority left
+    pub fn check_authority(&self, authority: &Pubkey) -> Result<(), ProgramError> {
+        match self.has_authority() {
+            Some(current) if current == *authority => Ok(()),
+            _ => Err(PinocchioError::InvalidAuthority.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a fresh constant product pool, as `InitializeConfig` leaves it
+    fn pool() -> Config {
+        new_pool(CurveType::ConstantProduct, 0)
+    }
+
+    fn stable_pool(amp: u64) -> Config {
+        new_pool(CurveType::Stable, amp)
+    }
+
+    fn new_pool(curve_type: CurveType, amp: u64) -> Config {
+        // the config is plain data, all zeroes is a valid value for every field
+        let mut config: Config = unsafe { core::mem::zeroed() };
+
+        config
+            .set_inner(
+                0,
+                [1; 32],
+                [2; 32],
+                [3; 32],
+                [4; 32],
+                [5; 32],
+                [6; 32],
+                30,
+                0,
+                0,
+                0,
+                AmmState::Initialized as u8,
+                curve_type,
+                amp,
+                0,
+                0,
+            )
+            .unwrap();
+
+        config
+    }
+
+    // the accounting of `Deposit`, returns the lp tokens minted to the depositor
+    fn deposit(config: &mut Config, minted: u64, amount_x: u64, amount_y: u64) -> u64 {
+        let (reserve_x, reserve_y) = config.reserves();
+        let lp_supply = config.lp_supply(minted).unwrap();
+
+        let lp_amount = curve::deposit(
+            config, reserve_x, reserve_y, lp_supply, amount_x, amount_y, 0,
+        )
+        .unwrap()
+        .lp_amount;
+
+        config.deposit_reserves(amount_x, amount_y).unwrap();
+
+        match lp_supply {
+            0 => config.lock_minimum_liquidity(lp_amount).unwrap(),
+            _ => lp_amount,
+        }
+    }
+
+    // the accounting of `Withdraw`, returns the tokens paid out for burning `amount`
+    fn withdraw(config: &mut Config, minted: u64, amount: u64) -> (u64, u64) {
+        let (reserve_x, reserve_y) = config.reserves();
+        let lp_supply = config.lp_supply(minted).unwrap();
+
+        let (amount_x, amount_y) =
+            curve::withdraw(config, reserve_x, reserve_y, lp_supply, amount).unwrap();
+
+        config.withdraw_reserves(amount_x, amount_y).unwrap();
+
+        (amount_x, amount_y)
+    }
+
+    #[test]
+    fn donation_does_not_change_the_share_price() {
+        let mut config = pool();
+
+        // the first deposit only just clears the locked liquidity, its depositor owns one token
+        let first = deposit(&mut config, 0, 1_001, 1_001);
+        assert_eq!(first, 1);
+
+        // a donation only shows up in the vault balances, not in the reserves deposits use
+        let donation = 1_000_000_000;
+        let (vault_x, vault_y) = (1_001 + donation, 1_001 + donation);
+        assert_eq!(
+            config.vault_reserves(vault_x, vault_y).unwrap(),
+            (vault_x, vault_y)
+        );
+        assert_eq!(config.reserves(), (1_001, 1_001));
+
+        let second = deposit(&mut config, first, 1_000_000, 1_000_000);
+        assert_eq!(second, 1_000_000);
+
+        let (amount_x, amount_y) = withdraw(&mut config, first + second, second);
+        assert_eq!((amount_x, amount_y), (1_000_000, 1_000_000));
+    }
+
+    #[test]
+    fn synced_donation_costs_the_donor_more_than_the_depositor_loses() {
+        let mut config = pool();
+
+        let first = deposit(&mut config, 0, 1_001, 1_001);
+
+        // `SyncReserves` hands the donation to the lps, including the locked liquidity
+        let donation = 10_000_000_000;
+        config.set_reserves(1_001 + donation, 1_001 + donation);
+
+        // sized so that a pool without locked liquidity would round the share down to nothing
+        let amount = donation / 2;
+        let second = deposit(&mut config, first, amount, amount);
+        assert!(second > 0);
+
+        let lp_value = (1_001 + donation) / config.lp_supply(first).unwrap();
+
+        let (second_x, second_y) = withdraw(&mut config, first + second, second);
+        let (first_x, _) = withdraw(&mut config, first, first);
+
+        // the depositor loses at most one lp token worth to rounding
+        assert!(amount - second_x <= lp_value);
+        assert!(amount - second_y <= lp_value);
+
+        // while the donor loses nearly all of the donation to the locked liquidity
+        assert!(1_001 + donation - first_x > 1_000 * (amount - second_x));
+    }
+
+    #[test]
+    fn locked_liquidity_is_never_withdrawn() {
+        let mut config = pool();
+
+        let first = deposit(&mut config, 0, 1_000_000, 4_000_000);
+        assert_eq!(first, 2_000_000 - curve::MINIMUM_LIQUIDITY);
+        assert_eq!(config.locked_liquidity(), curve::MINIMUM_LIQUIDITY);
+
+        let second = deposit(&mut config, first, 500_000, 2_000_000);
+
+        // burning every minted token
+        withdraw(&mut config, first + second, second);
+        withdraw(&mut config, first, first);
+
+        // leaves the share of the locked liquidity in the pool
+        assert_eq!(config.lp_supply(0).unwrap(), curve::MINIMUM_LIQUIDITY);
+        assert_eq!(config.reserves(), (500, 2_000));
+
+        // and nobody holds the tokens to burn for it
+        let (reserve_x, reserve_y) = config.reserves();
+        assert!(
+            curve::withdraw(&config, reserve_x, reserve_y, 0, curve::MINIMUM_LIQUIDITY).is_err()
+        );
+    }
+
+    #[test]
+    fn first_deposit_has_to_exceed_the_locked_liquidity() {
+        let mut config = pool();
+
+        assert!(curve::deposit(&config, 0, 0, 0, 1_000, 1_000, 0).is_err());
+        assert!(config
+            .lock_minimum_liquidity(curve::MINIMUM_LIQUIDITY)
+            .is_err());
+        assert_eq!(config.locked_liquidity(), 0);
+    }
+
+    #[test]
+    fn amp_ramps_from_start_to_stop() {
+        let mut config = stable_pool(100);
+        let (start, stop) = (1_000, 1_000 + 2 * Config::MIN_RAMP_DURATION);
+
+        config.ramp_amp(1_000, start, stop).unwrap();
+
+        assert_eq!(config.amp(0), 100);
+        assert_eq!(config.amp(start), 100);
+        assert_eq!(config.amp((start + stop) / 2), 550);
+        assert_eq!(config.amp(stop), 1_000);
+        assert_eq!(config.amp(stop + 1), 1_000);
+        assert_eq!(config.amp(i64::MAX), 1_000);
+
+        // and back down from where the first ramp ended
+        config
+            .ramp_amp(100, stop, stop + Config::MIN_RAMP_DURATION)
+            .unwrap();
+
+        assert_eq!(config.amp(stop), 1_000);
+        assert_eq!(config.amp(stop + Config::MIN_RAMP_DURATION / 2), 550);
+        assert_eq!(config.amp(stop + Config::MIN_RAMP_DURATION), 100);
+    }
+
+    #[test]
+    fn amp_moves_monotonically_during_a_ramp() {
+        let mut config = stable_pool(1_000_000);
+        let (start, stop) = (0, Config::MIN_RAMP_DURATION);
+
+        config.ramp_amp(100_000, start, stop).unwrap();
+
+        let mut previous = config.amp(start);
+
+        for timestamp in (start..=stop).step_by(997) {
+            let amp = config.amp(timestamp);
+
+            assert!(amp <= previous);
+            assert!((100_000..=1_000_000).contains(&amp));
+            previous = amp;
+        }
+    }
+
+    #[test]
+    fn stopping_a_ramp_keeps_the_current_amp() {
+        let mut config = stable_pool(100);
+        let stop = Config::MIN_RAMP_DURATION * 2;
+
+        config.ramp_amp(300, 0, stop).unwrap();
+        config.stop_ramp_amp(stop / 2).unwrap();
+
+        assert_eq!(config.amp(stop / 2), 200);
+        assert_eq!(config.amp(stop), 200);
+    }
+
+    #[test]
+    fn ramps_are_bounded() {
+        let mut config = stable_pool(100);
+
+        // more than ten times up or down at once
+        assert!(config
+            .ramp_amp(1_001, 0, Config::MIN_RAMP_DURATION)
+            .is_err());
+        assert!(config.ramp_amp(9, 0, Config::MIN_RAMP_DURATION).is_err());
+
+        // too fast
+        assert!(config
+            .ramp_amp(200, 0, Config::MIN_RAMP_DURATION - 1)
+            .is_err());
+
+        // and not over a running ramp
+        config.ramp_amp(200, 0, Config::MIN_RAMP_DURATION).unwrap();
+        assert!(config
+            .ramp_amp(300, 1, 1 + Config::MIN_RAMP_DURATION)
+            .is_err());
+
+        // only stable pools have an amplification
+        assert!(pool().ramp_amp(200, 0, Config::MIN_RAMP_DURATION).is_err());
+    }
+
+    #[test]
+    fn unbalanced_deposits_pay_the_protocol_on_both_sides() {
+        let mut config = stable_pool(100);
+        config.set_protocol_fee(5_000).unwrap();
+
+        let minted = deposit(&mut config, 0, 1_000_000_000, 1_000_000_000);
+        let (reserve_x, reserve_y) = config.reserves();
+        let lp_supply = config.lp_supply(minted).unwrap();
+
+        let quote =
+            curve::deposit(&config, reserve_x, reserve_y, lp_supply, 10_000_000, 0, 0).unwrap();
+
+        config.deposit_reserves(10_000_000, 0).unwrap();
+        config
+            .accrue_deposit_fees(quote.fee_x, quote.fee_y)
+            .unwrap();
+
+        // half of the imbalance fee goes to the protocol, out of the reserve it was charged on
+        let (protocol_x, protocol_y) = (quote.fee_x / 2, quote.fee_y / 2);
+
+        assert!(protocol_x > 0 && protocol_y > 0);
+        assert_eq!(
+            (config.protocol_fees_x(), config.protocol_fees_y()),
+            (protocol_x, protocol_y)
+        );
+        assert_eq!(
+            config.reserves(),
+            (reserve_x + 10_000_000 - protocol_x, reserve_y - protocol_y)
+        );
+    }
+
+    #[test]
+    fn referral_cap_is_bounded() {
+        let mut config = pool();
+
+        // new pools allow the highest referral share
+        assert_eq!(config.max_referral_fee(), Config::MAX_REFERRAL_FEE);
+
+        config.set_max_referral_fee(0).unwrap();
+        assert_eq!(config.max_referral_fee(), 0);
+
+        assert!(config
+            .set_max_referral_fee(Config::MAX_REFERRAL_FEE + 1)
+            .is_err());
+        assert_eq!(config.max_referral_fee(), 0);
+    }
+
+    #[test]
+    fn canonical_pools_sign_with_the_fee_tier_seed() {
+        let mut config = pool();
+        config.set_fee_tier(30);
+
+        // the bytes the canonical pool address is derived from in `CreatePool`
+        assert_eq!(config.fee_tier(), 30);
+        assert_eq!(config.fee_tier_bytes(), &30u16.to_le_bytes());
+    }
+}
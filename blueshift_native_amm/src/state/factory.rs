@@ -0,0 +1,144 @@
+use pinocchio::{
+    account_info::{AccountInfo, Ref, RefMut},
+    program_error::ProgramError,
+    pubkey::{create_program_address, Pubkey},
+};
+
+use crate::{error::PinocchioError, seeds, state::Config};
+
+// number of fee tiers the factory can hold
+pub const MAX_FEE_TIERS: usize = 8;
+
+// global account listing the fee tiers canonical pools can be created with. there is a single
+// factory per program, pools created through it live at an address derived from their sorted
+// mints and fee tier
+#[repr(C)]
+pub struct Factory {
+    authority: Pubkey,
+    // protocol share of the swap fee every pool created through the factory starts with
+    protocol_fee: u16,
+    fee_tiers: [u16; MAX_FEE_TIERS],
+    fee_tier_count: u8,
+    bump: [u8; 1],
+}
+
+impl Factory {
+    pub const LEN: usize = size_of::<Self>();
+
+    #[inline(always)]
+    pub fn load(account_info: &AccountInfo) -> Result<Ref<'_, Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if !account_info.is_owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        Ok(Ref::map(account_info.try_borrow_data()?, |data| unsafe {
+            Self::from_bytes_unchecked(data)
+        }))
+    }
+
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked(bytes: &[u8]) -> &Self {
+        &*(bytes.as_ptr() as *const Factory)
+    }
+
+    #[inline(always)]
+    pub fn load_mut(account_info: &AccountInfo) -> Result<RefMut<'_, Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if !account_info.is_owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        Ok(RefMut::map(
+            account_info.try_borrow_mut_data()?,
+            |data| unsafe { Self::from_bytes_unchecked_mut(data) },
+        ))
+    }
+
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked_mut(bytes: &mut [u8]) -> &mut Self {
+        &mut *(bytes.as_mut_ptr() as *mut Factory)
+    }
+
+    pub fn set_inner(
+        &mut self,
+        authority: Pubkey,
+        protocol_fee: u16,
+        bump: u8,
+    ) -> Result<(), ProgramError> {
+        if protocol_fee > Config::MAX_PROTOCOL_FEE {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        self.authority = authority;
+        self.protocol_fee = protocol_fee;
+        self.fee_tiers = [0; MAX_FEE_TIERS];
+        self.fee_tier_count = 0;
+        self.bump = [bump];
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn authority(&self) -> &Pubkey {
+        &self.authority
+    }
+
+    #[inline(always)]
+    pub fn protocol_fee(&self) -> u16 {
+        self.protocol_fee
+    }
+
+    #[inline(always)]
+    pub fn bump(&self) -> u8 {
+        self.bump[0]
+    }
+
+    #[inline(always)]
+    pub fn fee_tiers(&self) -> &[u16] {
+        &self.fee_tiers[..self.fee_tier_count as usize]
+    }
+
+    #[inline(always)]
+    pub fn has_fee_tier(&self, fee_tier: u16) -> bool {
+        self.fee_tiers().contains(&fee_tier)
+    }
+
+    // fee tiers can only be added, pools created with a tier keep their address
+    pub fn add_fee_tier(&mut self, fee_tier: u16) -> Result<(), ProgramError> {
+        if fee_tier == 0 || fee_tier > Config::MAX_FEE || self.has_fee_tier(fee_tier) {
+            return Err(PinocchioError::InvalidFeeTier.into());
+        }
+
+        if self.fee_tier_count as usize == MAX_FEE_TIERS {
+            return Err(PinocchioError::InvalidFeeTier.into());
+        }
+
+        self.fee_tiers[self.fee_tier_count as usize] = fee_tier;
+        self.fee_tier_count += 1;
+        Ok(())
+    }
+
+    // re-derives the factory pda from the stored bump
+    #[inline(always)]
+    pub fn check_address(&self, factory: &Pubkey) -> Result<(), ProgramError> {
+        let expected = create_program_address(&[seeds::FACTORY, &self.bump], &crate::ID)?;
+
+        if expected.ne(factory) {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        Ok(())
+    }
+
+    pub fn check_authority(&self, authority: &Pubkey) -> Result<(), ProgramError> {
+        if self.authority.ne(authority) {
+            return Err(PinocchioError::InvalidAuthority.into());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,174 @@
+use pinocchio::{
+    account_info::{AccountInfo, Ref, RefMut},
+    instruction::Seed,
+    program_error::ProgramError,
+    pubkey::{create_program_address, Pubkey},
+};
+
+use crate::{
+    math::{mul_div_ceil, ONE_Q64},
+    seeds,
+};
+
+// an order selling the escrowed tokens of one side of a pool for the other, at no less than
+// its limit price. the tokens sit in the order's associated token account until the order is
+// filled against the pool or cancelled
+#[repr(C)]
+pub struct LimitOrder {
+    config: Pubkey,
+    owner: Pubkey,
+    // the mint of the escrowed tokens, the side of the pool `is_x` points at
+    mint_in: Pubkey,
+    seed: [u8; 8],
+    // q64.64 limit price, output tokens per input token like the pool spot prices
+    price: [u8; 16],
+    // escrowed input tokens that are still for sale
+    amount: u64,
+    // 1 when the order sells the pool's x token
+    is_x: u8,
+    bump: [u8; 1],
+}
+
+impl LimitOrder {
+    pub const LEN: usize = size_of::<Self>();
+
+    #[inline(always)]
+    pub fn load(account_info: &AccountInfo) -> Result<Ref<'_, Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if !account_info.is_owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        Ok(Ref::map(account_info.try_borrow_data()?, |data| unsafe {
+            Self::from_bytes_unchecked(data)
+        }))
+    }
+
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked(bytes: &[u8]) -> &Self {
+        &*(bytes.as_ptr() as *const LimitOrder)
+    }
+
+    #[inline(always)]
+    pub fn load_mut(account_info: &AccountInfo) -> Result<RefMut<'_, Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if !account_info.is_owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        Ok(RefMut::map(
+            account_info.try_borrow_mut_data()?,
+            |data| unsafe { Self::from_bytes_unchecked_mut(data) },
+        ))
+    }
+
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked_mut(bytes: &mut [u8]) -> &mut Self {
+        &mut *(bytes.as_mut_ptr() as *mut LimitOrder)
+    }
+
+    pub fn set_inner(
+        &mut self,
+        config: Pubkey,
+        owner: Pubkey,
+        mint_in: Pubkey,
+        seed: u64,
+        is_x: bool,
+        amount: u64,
+        price: u128,
+        bump: u8,
+    ) {
+        self.config = config;
+        self.owner = owner;
+        self.mint_in = mint_in;
+        self.seed = seed.to_le_bytes();
+        self.price = price.to_le_bytes();
+        self.amount = amount;
+        self.is_x = is_x as u8;
+        self.bump = [bump];
+    }
+
+    #[inline(always)]
+    pub fn config(&self) -> &Pubkey {
+        &self.config
+    }
+
+    #[inline(always)]
+    pub fn owner(&self) -> &Pubkey {
+        &self.owner
+    }
+
+    #[inline(always)]
+    pub fn mint_in(&self) -> &Pubkey {
+        &self.mint_in
+    }
+
+    #[inline(always)]
+    pub fn seed(&self) -> u64 {
+        u64::from_le_bytes(self.seed)
+    }
+
+    #[inline(always)]
+    pub fn price(&self) -> u128 {
+        u128::from_le_bytes(self.price)
+    }
+
+    #[inline(always)]
+    pub fn amount(&self) -> u64 {
+        self.amount
+    }
+
+    #[inline(always)]
+    pub fn is_x(&self) -> bool {
+        self.is_x == 1
+    }
+
+    #[inline(always)]
+    pub fn bump(&self) -> u8 {
+        self.bump[0]
+    }
+
+    // the least the owner has to receive for the tokens still for sale, rounded up
+    #[inline(always)]
+    pub fn min_out(&self) -> Option<u64> {
+        Self::min_out_at(self.amount, self.price())
+    }
+
+    // the least `amount` sells for at `price`, none when it doesn't fit a token amount and the
+    // order could never be filled
+    #[inline(always)]
+    pub fn min_out_at(amount: u64, price: u128) -> Option<u64> {
+        u64::try_from(mul_div_ceil(amount as u128, price, ONE_Q64)?).ok()
+    }
+
+    // orders are filled all at once
+    #[inline(always)]
+    pub fn fill(&mut self) {
+        self.amount = 0;
+    }
+
+    // seeds used by the order pda to sign for its escrow
+    #[inline(always)]
+    pub fn signer_seeds(&self) -> [Seed<'_>; 5] {
+        seeds::order_signer(&self.config, &self.owner, &self.seed, &self.bump)
+    }
+
+    #[inline(always)]
+    pub fn check_address(&self, order: &Pubkey) -> Result<(), ProgramError> {
+        let expected = create_program_address(
+            &seeds::order_with_bump(&self.config, &self.owner, &self.seed, &self.bump),
+            &crate::ID,
+        )?;
+
+        if expected.ne(order) {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,15 @@
+pub mod concentrated_pool;
+pub mod config;
+pub mod factory;
+pub mod limit_order;
+pub mod oracle;
+pub mod position;
+pub mod tick_array;
+
+pub use concentrated_pool::*;
+pub use config::*;
+pub use factory::*;
+pub use limit_order::*;
+pub use oracle::*;
+pub use position::*;
+pub use tick_array::*;
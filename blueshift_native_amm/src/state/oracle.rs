@@ -0,0 +1,207 @@
+use pinocchio::{
+    account_info::{AccountInfo, Ref, RefMut},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::state::Config;
+
+// number of observations kept in the ring buffer
+pub const OBSERVATIONS: usize = 64;
+
+#[repr(C)]
+pub struct Observation {
+    timestamp: i64,
+    slot: u64,
+    price_x_cumulative: [u8; 16],
+    price_y_cumulative: [u8; 16],
+}
+
+impl Observation {
+    pub fn new(
+        timestamp: i64,
+        slot: u64,
+        price_x_cumulative: u128,
+        price_y_cumulative: u128,
+    ) -> Self {
+        Self {
+            timestamp,
+            slot,
+            price_x_cumulative: price_x_cumulative.to_le_bytes(),
+            price_y_cumulative: price_y_cumulative.to_le_bytes(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn timestamp(&self) -> i64 {
+        self.timestamp
+    }
+
+    #[inline(always)]
+    pub fn slot(&self) -> u64 {
+        self.slot
+    }
+
+    #[inline(always)]
+    pub fn price_x_cumulative(&self) -> u128 {
+        u128::from_le_bytes(self.price_x_cumulative)
+    }
+
+    #[inline(always)]
+    pub fn price_y_cumulative(&self) -> u128 {
+        u128::from_le_bytes(self.price_y_cumulative)
+    }
+
+    // time weighted average q64.64 prices of x and y between this and a newer observation
+    pub fn twap(&self, newer: &Observation) -> Option<(u128, u128)> {
+        let elapsed = newer.timestamp.checked_sub(self.timestamp)?;
+
+        if elapsed <= 0 {
+            return None;
+        }
+
+        Some((
+            newer
+                .price_x_cumulative()
+                .wrapping_sub(self.price_x_cumulative())
+                / elapsed as u128,
+            newer
+                .price_y_cumulative()
+                .wrapping_sub(self.price_y_cumulative())
+                / elapsed as u128,
+        ))
+    }
+}
+
+// companion account of a pool holding snapshots of its price accumulators,
+// so callers can pick the window they want the twap over
+#[repr(C)]
+pub struct Oracle {
+    config: Pubkey,
+    index: u16,
+    len: u16,
+    bump: [u8; 1],
+    _padding: [u8; 3],
+    observations: [Observation; OBSERVATIONS],
+}
+
+impl Oracle {
+    pub const LEN: usize = size_of::<Self>();
+
+    #[inline(always)]
+    pub fn load(account_info: &AccountInfo) -> Result<Ref<'_, Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if !account_info.is_owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        Ok(Ref::map(account_info.try_borrow_data()?, |data| unsafe {
+            Self::from_bytes_unchecked(data)
+        }))
+    }
+
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked(bytes: &[u8]) -> &Self {
+        &*(bytes.as_ptr() as *const Oracle)
+    }
+
+    #[inline(always)]
+    pub fn load_mut(account_info: &AccountInfo) -> Result<RefMut<'_, Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if !account_info.is_owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        Ok(RefMut::map(
+            account_info.try_borrow_mut_data()?,
+            |data| unsafe { Self::from_bytes_unchecked_mut(data) },
+        ))
+    }
+
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked_mut(bytes: &mut [u8]) -> &mut Self {
+        &mut *(bytes.as_mut_ptr() as *mut Oracle)
+    }
+
+    pub fn set_inner(&mut self, config: Pubkey, bump: u8) {
+        self.config = config;
+        self.index = 0;
+        self.len = 0;
+        self.bump = [bump];
+    }
+
+    #[inline(always)]
+    pub fn config(&self) -> &Pubkey {
+        &self.config
+    }
+
+    #[inline(always)]
+    pub fn bump(&self) -> u8 {
+        self.bump[0]
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // the most recent observation
+    pub fn latest(&self) -> Option<&Observation> {
+        if self.is_empty() {
+            return None;
+        }
+        Some(&self.observations[self.index as usize])
+    }
+
+    // walks the ring buffer from the newest entry and returns the first one at or before `timestamp`
+    pub fn observation_before(&self, timestamp: i64) -> Option<&Observation> {
+        (0..self.len())
+            .map(|i| &self.observations[(self.index as usize + OBSERVATIONS - i) % OBSERVATIONS])
+            .find(|observation| observation.timestamp <= timestamp)
+    }
+
+    // snapshots the pool accumulators, at most one observation is kept per second
+    pub fn record(&mut self, config_key: &Pubkey, config: &Config) -> Result<(), ProgramError> {
+        if self.config.ne(config_key) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let timestamp = config.last_update_timestamp();
+
+        if let Some(latest) = self.latest() {
+            if latest.timestamp >= timestamp {
+                return Ok(());
+            }
+        }
+
+        let index = match self.is_empty() {
+            true => 0,
+            false => (self.index as usize + 1) % OBSERVATIONS,
+        };
+
+        self.observations[index] = Observation::new(
+            timestamp,
+            config.last_update_slot(),
+            config.price_x_cumulative(),
+            config.price_y_cumulative(),
+        );
+
+        self.index = index as u16;
+        if self.len() < OBSERVATIONS {
+            self.len += 1;
+        }
+
+        Ok(())
+    }
+}
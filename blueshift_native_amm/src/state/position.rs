@@ -0,0 +1,266 @@
+use pinocchio::{
+    account_info::{AccountInfo, Ref, RefMut},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    curve::concentrated::{amounts_for_liquidity, fee_growth_inside},
+    error::PinocchioError,
+    math::{mul_div, ONE_Q64},
+    state::{ConcentratedPool, TickArray},
+};
+
+// liquidity an owner provides to a concentrated pool between two ticks
+#[repr(C)]
+pub struct Position {
+    pool: Pubkey,
+    owner: Pubkey,
+    tick_lower: [u8; 4],
+    tick_upper: [u8; 4],
+    liquidity: [u8; 16],
+    // fee growth inside the range when the fees were last settled, q64.64
+    fee_growth_inside_x_last: [u8; 16],
+    fee_growth_inside_y_last: [u8; 16],
+    fees_owed_x: u64,
+    fees_owed_y: u64,
+}
+
+impl Position {
+    pub const LEN: usize = size_of::<Self>();
+
+    #[inline(always)]
+    pub fn load(account_info: &AccountInfo) -> Result<Ref<'_, Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if !account_info.is_owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        Ok(Ref::map(account_info.try_borrow_data()?, |data| unsafe {
+            Self::from_bytes_unchecked(data)
+        }))
+    }
+
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked(bytes: &[u8]) -> &Self {
+        &*(bytes.as_ptr() as *const Position)
+    }
+
+    #[inline(always)]
+    pub fn load_mut(account_info: &AccountInfo) -> Result<RefMut<'_, Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if !account_info.is_owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        Ok(RefMut::map(
+            account_info.try_borrow_mut_data()?,
+            |data| unsafe { Self::from_bytes_unchecked_mut(data) },
+        ))
+    }
+
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked_mut(bytes: &mut [u8]) -> &mut Self {
+        &mut *(bytes.as_mut_ptr() as *mut Position)
+    }
+
+    pub fn set_inner(&mut self, pool: Pubkey, owner: Pubkey, tick_lower: i32, tick_upper: i32) {
+        self.pool = pool;
+        self.owner = owner;
+        self.tick_lower = tick_lower.to_le_bytes();
+        self.tick_upper = tick_upper.to_le_bytes();
+        self.liquidity = [0; 16];
+        self.fee_growth_inside_x_last = [0; 16];
+        self.fee_growth_inside_y_last = [0; 16];
+        self.fees_owed_x = 0;
+        self.fees_owed_y = 0;
+    }
+
+    #[inline(always)]
+    pub fn pool(&self) -> &Pubkey {
+        &self.pool
+    }
+
+    #[inline(always)]
+    pub fn owner(&self) -> &Pubkey {
+        &self.owner
+    }
+
+    #[inline(always)]
+    pub fn tick_lower(&self) -> i32 {
+        i32::from_le_bytes(self.tick_lower)
+    }
+
+    #[inline(always)]
+    pub fn tick_upper(&self) -> i32 {
+        i32::from_le_bytes(self.tick_upper)
+    }
+
+    #[inline(always)]
+    pub fn liquidity(&self) -> u128 {
+        u128::from_le_bytes(self.liquidity)
+    }
+
+    #[inline(always)]
+    pub fn fees_owed_x(&self) -> u64 {
+        self.fees_owed_x
+    }
+
+    #[inline(always)]
+    pub fn fees_owed_y(&self) -> u64 {
+        self.fees_owed_y
+    }
+
+    // checks the position belongs to the pool and the signer
+    pub fn check(&self, pool: &Pubkey, owner: &Pubkey) -> Result<(), ProgramError> {
+        if self.pool.ne(pool) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if self.owner.ne(owner) {
+            return Err(PinocchioError::InvalidOwner.into());
+        }
+        Ok(())
+    }
+
+    // settles the fees earned since the last update, then applies the liquidity change
+    pub fn update(
+        &mut self,
+        liquidity_delta: i128,
+        fee_growth_inside_x: u128,
+        fee_growth_inside_y: u128,
+    ) -> Result<(), ProgramError> {
+        let liquidity = self.liquidity();
+
+        let earned_x = mul_div(
+            fee_growth_inside_x.wrapping_sub(u128::from_le_bytes(self.fee_growth_inside_x_last)),
+            liquidity,
+            ONE_Q64,
+        )
+        .and_then(|earned| u64::try_from(earned).ok())
+        .ok_or(PinocchioError::MathOverflow)?;
+
+        let earned_y = mul_div(
+            fee_growth_inside_y.wrapping_sub(u128::from_le_bytes(self.fee_growth_inside_y_last)),
+            liquidity,
+            ONE_Q64,
+        )
+        .and_then(|earned| u64::try_from(earned).ok())
+        .ok_or(PinocchioError::MathOverflow)?;
+
+        self.fees_owed_x = self
+            .fees_owed_x
+            .checked_add(earned_x)
+            .ok_or(PinocchioError::MathOverflow)?;
+        self.fees_owed_y = self
+            .fees_owed_y
+            .checked_add(earned_y)
+            .ok_or(PinocchioError::MathOverflow)?;
+
+        self.fee_growth_inside_x_last = fee_growth_inside_x.to_le_bytes();
+        self.fee_growth_inside_y_last = fee_growth_inside_y.to_le_bytes();
+
+        self.liquidity = liquidity
+            .checked_add_signed(liquidity_delta)
+            .ok_or(PinocchioError::MathOverflow)?
+            .to_le_bytes();
+        Ok(())
+    }
+
+    // hands out the settled fees
+    pub fn take_fees(&mut self) -> (u64, u64) {
+        let fees = (self.fees_owed_x, self.fees_owed_y);
+        self.fees_owed_x = 0;
+        self.fees_owed_y = 0;
+        fees
+    }
+}
+
+// applies a liquidity change to a position, its two boundary ticks and the pool, and returns
+// the token amounts the change is worth. both tick arrays can be the same account
+pub fn modify_position(
+    pool: &AccountInfo,
+    position: &AccountInfo,
+    tick_array_lower: &AccountInfo,
+    tick_array_upper: &AccountInfo,
+    liquidity_delta: i128,
+) -> Result<(u64, u64), ProgramError> {
+    let mut pool_data = ConcentratedPool::load_mut(pool)?;
+    let mut position_data = Position::load_mut(position)?;
+
+    let tick_spacing = pool_data.tick_spacing();
+    let tick_current = pool_data.tick_current();
+    let fee_growth_global_x = pool_data.fee_growth_global_x();
+    let fee_growth_global_y = pool_data.fee_growth_global_y();
+
+    let (tick_lower, tick_upper) = (position_data.tick_lower(), position_data.tick_upper());
+
+    for (tick_array, tick, is_upper) in [
+        (tick_array_lower, tick_lower, false),
+        (tick_array_upper, tick_upper, true),
+    ] {
+        let mut tick_array_data = TickArray::load_mut(tick_array)?;
+
+        if tick_array_data.pool().ne(pool.key()) {
+            return Err(PinocchioError::InvalidTickArray.into());
+        }
+
+        tick_array_data.tick_mut(tick, tick_spacing)?.update(
+            tick,
+            tick_current,
+            liquidity_delta,
+            fee_growth_global_x,
+            fee_growth_global_y,
+            is_upper,
+        )?;
+    }
+
+    let (lower_outside_x, lower_outside_y) = {
+        let tick_array_data = TickArray::load(tick_array_lower)?;
+        let tick = tick_array_data.tick(tick_lower, tick_spacing)?;
+        (tick.fee_growth_outside_x(), tick.fee_growth_outside_y())
+    };
+
+    let (upper_outside_x, upper_outside_y) = {
+        let tick_array_data = TickArray::load(tick_array_upper)?;
+        let tick = tick_array_data.tick(tick_upper, tick_spacing)?;
+        (tick.fee_growth_outside_x(), tick.fee_growth_outside_y())
+    };
+
+    position_data.update(
+        liquidity_delta,
+        fee_growth_inside(
+            tick_lower,
+            tick_upper,
+            tick_current,
+            lower_outside_x,
+            upper_outside_x,
+            fee_growth_global_x,
+        ),
+        fee_growth_inside(
+            tick_lower,
+            tick_upper,
+            tick_current,
+            lower_outside_y,
+            upper_outside_y,
+            fee_growth_global_y,
+        ),
+    )?;
+
+    pool_data.apply_liquidity_delta(tick_lower, tick_upper, liquidity_delta)?;
+
+    amounts_for_liquidity(
+        pool_data.sqrt_price(),
+        tick_current,
+        tick_lower,
+        tick_upper,
+        liquidity_delta.unsigned_abs(),
+        liquidity_delta > 0,
+    )
+}
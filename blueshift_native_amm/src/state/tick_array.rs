@@ -0,0 +1,230 @@
+use pinocchio::{
+    account_info::{AccountInfo, Ref, RefMut},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::error::PinocchioError;
+
+// number of initializable ticks held by one tick array account
+pub const TICK_ARRAY_SIZE: usize = 64;
+
+#[repr(C)]
+pub struct Tick {
+    // liquidity added when the price crosses the tick going up, removed going down
+    liquidity_net: [u8; 16],
+    // total liquidity of the positions using the tick as a boundary
+    liquidity_gross: [u8; 16],
+    // fee growth on the other side of the tick from the current price, q64.64
+    fee_growth_outside_x: [u8; 16],
+    fee_growth_outside_y: [u8; 16],
+    initialized: u8,
+}
+
+impl Tick {
+    #[inline(always)]
+    pub fn liquidity_net(&self) -> i128 {
+        i128::from_le_bytes(self.liquidity_net)
+    }
+
+    #[inline(always)]
+    pub fn liquidity_gross(&self) -> u128 {
+        u128::from_le_bytes(self.liquidity_gross)
+    }
+
+    #[inline(always)]
+    pub fn fee_growth_outside_x(&self) -> u128 {
+        u128::from_le_bytes(self.fee_growth_outside_x)
+    }
+
+    #[inline(always)]
+    pub fn fee_growth_outside_y(&self) -> u128 {
+        u128::from_le_bytes(self.fee_growth_outside_y)
+    }
+
+    #[inline(always)]
+    pub fn is_initialized(&self) -> bool {
+        self.initialized != 0
+    }
+
+    // adds a position boundary to the tick, or removes it for a negative delta
+    pub fn update(
+        &mut self,
+        tick: i32,
+        tick_current: i32,
+        liquidity_delta: i128,
+        fee_growth_global_x: u128,
+        fee_growth_global_y: u128,
+        is_upper: bool,
+    ) -> Result<(), ProgramError> {
+        let liquidity_gross = self
+            .liquidity_gross()
+            .checked_add_signed(liquidity_delta)
+            .ok_or(PinocchioError::MathOverflow)?;
+
+        // by convention all the growth before a tick is initialized happened below it
+        if !self.is_initialized() {
+            let (outside_x, outside_y) = match tick <= tick_current {
+                true => (fee_growth_global_x, fee_growth_global_y),
+                false => (0, 0),
+            };
+
+            self.fee_growth_outside_x = outside_x.to_le_bytes();
+            self.fee_growth_outside_y = outside_y.to_le_bytes();
+        }
+
+        let liquidity_net = match is_upper {
+            true => self.liquidity_net().checked_sub(liquidity_delta),
+            false => self.liquidity_net().checked_add(liquidity_delta),
+        }
+        .ok_or(PinocchioError::MathOverflow)?;
+
+        self.liquidity_net = liquidity_net.to_le_bytes();
+        self.liquidity_gross = liquidity_gross.to_le_bytes();
+
+        // once the last position using the tick is gone it can't be crossed anymore, the fee
+        // growth is kept for settling that position and reset when the tick is reused
+        self.initialized = (liquidity_gross != 0) as u8;
+        Ok(())
+    }
+
+    // the price moved through the tick, what was outside is now inside. returns liquidity_net
+    pub fn cross(&mut self, fee_growth_global_x: u128, fee_growth_global_y: u128) -> i128 {
+        self.fee_growth_outside_x = fee_growth_global_x
+            .wrapping_sub(self.fee_growth_outside_x())
+            .to_le_bytes();
+        self.fee_growth_outside_y = fee_growth_global_y
+            .wrapping_sub(self.fee_growth_outside_y())
+            .to_le_bytes();
+
+        self.liquidity_net()
+    }
+}
+
+// a fixed run of ticks of one pool, starting at `start_tick_index`
+#[repr(C)]
+pub struct TickArray {
+    pool: Pubkey,
+    start_tick_index: [u8; 4],
+    ticks: [Tick; TICK_ARRAY_SIZE],
+}
+
+impl TickArray {
+    pub const LEN: usize = size_of::<Self>();
+
+    #[inline(always)]
+    pub fn load(account_info: &AccountInfo) -> Result<Ref<'_, Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if !account_info.is_owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        Ok(Ref::map(account_info.try_borrow_data()?, |data| unsafe {
+            Self::from_bytes_unchecked(data)
+        }))
+    }
+
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked(bytes: &[u8]) -> &Self {
+        &*(bytes.as_ptr() as *const TickArray)
+    }
+
+    #[inline(always)]
+    pub fn load_mut(account_info: &AccountInfo) -> Result<RefMut<'_, Self>, ProgramError> {
+        if account_info.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if !account_info.is_owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        Ok(RefMut::map(
+            account_info.try_borrow_mut_data()?,
+            |data| unsafe { Self::from_bytes_unchecked_mut(data) },
+        ))
+    }
+
+    #[inline(always)]
+    pub unsafe fn from_bytes_unchecked_mut(bytes: &mut [u8]) -> &mut Self {
+        &mut *(bytes.as_mut_ptr() as *mut TickArray)
+    }
+
+    // the account is created zeroed, so every tick starts uninitialized
+    pub fn set_inner(&mut self, pool: Pubkey, start_tick_index: i32) {
+        self.pool = pool;
+        self.start_tick_index = start_tick_index.to_le_bytes();
+    }
+
+    #[inline(always)]
+    pub fn pool(&self) -> &Pubkey {
+        &self.pool
+    }
+
+    #[inline(always)]
+    pub fn start_tick_index(&self) -> i32 {
+        i32::from_le_bytes(self.start_tick_index)
+    }
+
+    // number of ticks covered by one array
+    #[inline(always)]
+    pub fn span(tick_spacing: u16) -> i32 {
+        TICK_ARRAY_SIZE as i32 * tick_spacing as i32
+    }
+
+    // start index of the array holding `tick`
+    #[inline(always)]
+    pub fn start_index_for(tick: i32, tick_spacing: u16) -> i32 {
+        let span = Self::span(tick_spacing);
+        tick.div_euclid(span) * span
+    }
+
+    #[inline(always)]
+    pub fn contains(&self, tick: i32, tick_spacing: u16) -> bool {
+        let start = self.start_tick_index();
+        tick >= start && tick < start + Self::span(tick_spacing)
+    }
+
+    // position of an initializable tick in the array
+    fn offset(&self, tick: i32, tick_spacing: u16) -> Result<usize, ProgramError> {
+        if !self.contains(tick, tick_spacing) || tick % tick_spacing as i32 != 0 {
+            return Err(PinocchioError::InvalidTickArray.into());
+        }
+
+        Ok(((tick - self.start_tick_index()) / tick_spacing as i32) as usize)
+    }
+
+    pub fn tick(&self, tick: i32, tick_spacing: u16) -> Result<&Tick, ProgramError> {
+        Ok(&self.ticks[self.offset(tick, tick_spacing)?])
+    }
+
+    pub fn tick_mut(&mut self, tick: i32, tick_spacing: u16) -> Result<&mut Tick, ProgramError> {
+        Ok(&mut self.ticks[self.offset(tick, tick_spacing)?])
+    }
+
+    // next initialized tick from `search_start` in the swap direction, or the last tick of the
+    // array when there is none, which the swap then steps to before moving to the next array
+    pub fn next_initialized_tick(
+        &self,
+        search_start: i32,
+        tick_spacing: u16,
+        is_x: bool,
+    ) -> Result<(i32, bool), ProgramError> {
+        let offset = self.offset(search_start, tick_spacing)?;
+        let tick_at = |i: usize| self.start_tick_index() + i as i32 * tick_spacing as i32;
+
+        let found = match is_x {
+            true => (0..=offset).rev().find(|i| self.ticks[*i].is_initialized()),
+            false => (offset..TICK_ARRAY_SIZE).find(|i| self.ticks[*i].is_initialized()),
+        };
+
+        Ok(match (found, is_x) {
+            (Some(i), _) => (tick_at(i), true),
+            (None, true) => (tick_at(0), false),
+            (None, false) => (tick_at(TICK_ARRAY_SIZE - 1), false),
+        })
+    }
+}
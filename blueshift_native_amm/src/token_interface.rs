@@ -0,0 +1,461 @@
+// the legacy token program and token-2022 share the instruction layouts and the base account
+// layouts used here, so every token account and cpi of the amm goes through this module and
+// works with either program. token-2022 accounts with extensions are longer than the base
+// layout and carry an account type byte right after it
+
+use pinocchio::{
+    account_info::{AccountInfo, Ref},
+    cpi::invoke_signed,
+    instruction::{AccountMeta, Instruction, Signer},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    ProgramResult,
+};
+use pinocchio_token::state::{Mint, TokenAccount};
+
+use crate::{error::PinocchioError, math::mul_div_ceil};
+
+// TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb
+pub const TOKEN_2022_ID: Pubkey = [
+    0x06, 0xdd, 0xf6, 0xe1, 0xee, 0x75, 0x8f, 0xde, 0x18, 0x42, 0x5d, 0xbc, 0xe4, 0x6c, 0xcd, 0xda,
+    0xb6, 0x1a, 0xfc, 0x4d, 0x83, 0xb9, 0x0d, 0x27, 0xfe, 0xbd, 0xf9, 0x28, 0xd8, 0xa1, 0x8b, 0xfc,
+];
+
+// mints are padded up to the token account length so the type byte sits at the same offset
+const ACCOUNT_TYPE_OFFSET: usize = TokenAccount::LEN;
+
+const ACCOUNT_TYPE_MINT: u8 = 1;
+const ACCOUNT_TYPE_ACCOUNT: u8 = 2;
+
+const EXTENSION_TRANSFER_FEE_CONFIG: u16 = 1;
+
+// mint extensions a pool can hold. the others either lock up the vaults (non-transferable,
+// frozen by default, pausable), let someone else move the pool's tokens (permanent delegate)
+// or need accounts the pool doesn't pass along (transfer hooks, confidential transfers)
+const SUPPORTED_MINT_EXTENSIONS: [u16; 10] = [
+    EXTENSION_TRANSFER_FEE_CONFIG,
+    3,  // mint close authority
+    10, // interest bearing config
+    18, // metadata pointer
+    19, // token metadata
+    20, // group pointer
+    21, // token group
+    22, // group member pointer
+    23, // token group member
+    25, // scaled ui amount
+];
+
+#[inline(always)]
+pub fn is_token_program(program: &Pubkey) -> bool {
+    program == &pinocchio_token::ID || program == &TOKEN_2022_ID
+}
+
+// the token program owning a mint or a token account
+#[inline(always)]
+pub fn token_program_of(account: &AccountInfo) -> Result<&'static Pubkey, ProgramError> {
+    if account.is_owned_by(&pinocchio_token::ID) {
+        return Ok(&pinocchio_token::ID);
+    }
+
+    if account.is_owned_by(&TOKEN_2022_ID) {
+        return Ok(&TOKEN_2022_ID);
+    }
+
+    Err(ProgramError::IllegalOwner)
+}
+
+// checks the token program passed for a mint is the one that owns it
+#[inline(always)]
+pub fn check_token_program(token_program: &AccountInfo, mint: &AccountInfo) -> ProgramResult {
+    if token_program.key() != token_program_of(mint)? {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(())
+}
+
+// base layout length, or an extended token-2022 account of the expected type
+fn check_layout(account: &AccountInfo, base_len: usize, account_type: u8) -> ProgramResult {
+    let data_len = account.data_len();
+
+    if data_len == base_len {
+        return Ok(());
+    }
+
+    if token_program_of(account)? != &TOKEN_2022_ID || data_len <= ACCOUNT_TYPE_OFFSET {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if account.try_borrow_data()?[ACCOUNT_TYPE_OFFSET] != account_type {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(())
+}
+
+#[inline(always)]
+pub fn check_mint(account: &AccountInfo) -> ProgramResult {
+    token_program_of(account)?;
+    check_layout(account, Mint::LEN, ACCOUNT_TYPE_MINT)
+}
+
+#[inline(always)]
+pub fn check_token_account(account: &AccountInfo) -> ProgramResult {
+    token_program_of(account)?;
+    check_layout(account, TokenAccount::LEN, ACCOUNT_TYPE_ACCOUNT)
+}
+
+// token-2022 extensions are tlv entries after the account type byte: u16 type, u16 length
+struct Extensions<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Extensions<'a> {
+    fn new(account_data: &'a [u8]) -> Self {
+        Self {
+            data: account_data.get(ACCOUNT_TYPE_OFFSET + 1..).unwrap_or(&[]),
+        }
+    }
+}
+
+impl<'a> Iterator for Extensions<'a> {
+    type Item = (u16, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let header = self.data.get(0..4)?;
+        let extension_type = u16::from_le_bytes([header[0], header[1]]);
+        let length = u16::from_le_bytes([header[2], header[3]]) as usize;
+
+        // the space after the last entry is left uninitialized
+        if extension_type == 0 {
+            return None;
+        }
+
+        let value = self.data.get(4..4 + length)?;
+        self.data = &self.data[4 + length..];
+
+        Some((extension_type, value))
+    }
+}
+
+// rejects mints with extensions the pool can't hold, legacy mints have none
+pub fn check_extensions(mint: &AccountInfo) -> ProgramResult {
+    check_mint(mint)?;
+
+    let data = mint.try_borrow_data()?;
+
+    if Extensions::new(&data)
+        .any(|(extension_type, _)| !SUPPORTED_MINT_EXTENSIONS.contains(&extension_type))
+    {
+        return Err(PinocchioError::UnsupportedExtension.into());
+    }
+    Ok(())
+}
+
+#[inline(always)]
+pub fn has_transfer_fee(mint: &AccountInfo) -> Result<bool, ProgramError> {
+    check_mint(mint)?;
+
+    Ok(Extensions::new(&mint.try_borrow_data()?)
+        .any(|(extension_type, _)| extension_type == EXTENSION_TRANSFER_FEE_CONFIG))
+}
+
+// the part of a transfer a token-2022 transfer fee mint withholds in the destination account
+#[derive(Clone, Copy, Default)]
+pub struct TransferFee {
+    pub maximum_fee: u64,
+    pub basis_points: u16,
+}
+
+impl TransferFee {
+    pub const MAX_BASIS_POINTS: u16 = 10_000;
+
+    // withheld from a transfer of `amount`, rounded up
+    pub fn fee(&self, amount: u64) -> Option<u64> {
+        if self.basis_points == 0 || amount == 0 {
+            return Some(0);
+        }
+
+        let fee = mul_div_ceil(
+            amount as u128,
+            self.basis_points as u128,
+            Self::MAX_BASIS_POINTS as u128,
+        )?;
+
+        Some((fee as u64).min(self.maximum_fee))
+    }
+
+    // fee of the smallest transfer that still delivers `amount`
+    pub fn inverse_fee(&self, amount: u64) -> Option<u64> {
+        if self.basis_points == 0 {
+            return Some(0);
+        }
+
+        if self.basis_points == Self::MAX_BASIS_POINTS {
+            return Some(self.maximum_fee);
+        }
+
+        let pre_fee_amount = mul_div_ceil(
+            amount as u128,
+            Self::MAX_BASIS_POINTS as u128,
+            (Self::MAX_BASIS_POINTS - self.basis_points) as u128,
+        )?;
+
+        Some(((pre_fee_amount - amount as u128) as u64).min(self.maximum_fee))
+    }
+
+    // what the destination ends up with after sending `amount`
+    #[inline(always)]
+    pub fn amount_received(&self, amount: u64) -> Option<u64> {
+        amount.checked_sub(self.fee(amount)?)
+    }
+}
+
+// the transfer fee a mint charges in `epoch`, zero for mints without the extension.
+// the config holds the fee for older epochs followed by the one that applies from its epoch on
+pub fn transfer_fee(mint: &AccountInfo, epoch: u64) -> Result<TransferFee, ProgramError> {
+    check_mint(mint)?;
+
+    let data = mint.try_borrow_data()?;
+
+    let Some((_, config)) = Extensions::new(&data)
+        .find(|(extension_type, _)| *extension_type == EXTENSION_TRANSFER_FEE_CONFIG)
+    else {
+        return Ok(TransferFee::default());
+    };
+
+    // authorities and the withheld amount come first, then the older and newer fees, each
+    // an epoch, a maximum fee and basis points
+    if config.len() != 108 {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let newer_epoch = u64::from_le_bytes(config[90..98].try_into().unwrap());
+
+    let fee = match epoch >= newer_epoch {
+        true => &config[98..108],
+        false => &config[80..90],
+    };
+
+    Ok(TransferFee {
+        maximum_fee: u64::from_le_bytes(fee[0..8].try_into().unwrap()),
+        basis_points: u16::from_le_bytes([fee[8], fee[9]]),
+    })
+}
+
+// the base mint fields of either program
+#[inline(always)]
+pub fn mint(account: &AccountInfo) -> Result<Ref<'_, Mint>, ProgramError> {
+    check_mint(account)?;
+
+    Ok(Ref::map(account.try_borrow_data()?, |data| unsafe {
+        Mint::from_bytes_unchecked(data)
+    }))
+}
+
+// the base token account fields of either program
+#[inline(always)]
+pub fn token_account(account: &AccountInfo) -> Result<Ref<'_, TokenAccount>, ProgramError> {
+    check_token_account(account)?;
+
+    Ok(Ref::map(account.try_borrow_data()?, |data| unsafe {
+        TokenAccount::from_bytes_unchecked(data)
+    }))
+}
+
+// transfers have to be checked, token-2022 rejects unchecked transfers of some extensions
+pub struct TransferChecked<'a> {
+    pub from: &'a AccountInfo,
+    pub mint: &'a AccountInfo,
+    pub to: &'a AccountInfo,
+    pub authority: &'a AccountInfo,
+    pub amount: u64,
+    pub decimals: u8,
+    pub token_program: &'a Pubkey,
+}
+
+impl TransferChecked<'_> {
+    pub const DISCRIMINATOR: u8 = 12;
+
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let account_metas = [
+            AccountMeta::writable(self.from.key()),
+            AccountMeta::readonly(self.mint.key()),
+            AccountMeta::writable(self.to.key()),
+            AccountMeta::readonly_signer(self.authority.key()),
+        ];
+
+        let mut data = [0u8; 10];
+        data[0] = Self::DISCRIMINATOR;
+        data[1..9].copy_from_slice(&self.amount.to_le_bytes());
+        data[9] = self.decimals;
+
+        let instruction = Instruction {
+            program_id: self.token_program,
+            accounts: &account_metas,
+            data: &data,
+        };
+
+        invoke_signed(
+            &instruction,
+            &[self.from, self.mint, self.to, self.authority],
+            signers,
+        )
+    }
+}
+
+pub struct MintTo<'a> {
+    pub mint: &'a AccountInfo,
+    pub account: &'a AccountInfo,
+    pub mint_authority: &'a AccountInfo,
+    pub amount: u64,
+    pub token_program: &'a Pubkey,
+}
+
+impl MintTo<'_> {
+    pub const DISCRIMINATOR: u8 = 7;
+
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let account_metas = [
+            AccountMeta::writable(self.mint.key()),
+            AccountMeta::writable(self.account.key()),
+            AccountMeta::readonly_signer(self.mint_authority.key()),
+        ];
+
+        let mut data = [0u8; 9];
+        data[0] = Self::DISCRIMINATOR;
+        data[1..9].copy_from_slice(&self.amount.to_le_bytes());
+
+        let instruction = Instruction {
+            program_id: self.token_program,
+            accounts: &account_metas,
+            data: &data,
+        };
+
+        invoke_signed(
+            &instruction,
+            &[self.mint, self.account, self.mint_authority],
+            signers,
+        )
+    }
+}
+
+pub struct Burn<'a> {
+    pub account: &'a AccountInfo,
+    pub mint: &'a AccountInfo,
+    pub authority: &'a AccountInfo,
+    pub amount: u64,
+    pub token_program: &'a Pubkey,
+}
+
+impl Burn<'_> {
+    pub const DISCRIMINATOR: u8 = 8;
+
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let account_metas = [
+            AccountMeta::writable(self.account.key()),
+            AccountMeta::writable(self.mint.key()),
+            AccountMeta::readonly_signer(self.authority.key()),
+        ];
+
+        let mut data = [0u8; 9];
+        data[0] = Self::DISCRIMINATOR;
+        data[1..9].copy_from_slice(&self.amount.to_le_bytes());
+
+        let instruction = Instruction {
+            program_id: self.token_program,
+            accounts: &account_metas,
+            data: &data,
+        };
+
+        invoke_signed(
+            &instruction,
+            &[self.account, self.mint, self.authority],
+            signers,
+        )
+    }
+}
+
+pub struct CloseAccount<'a> {
+    pub account: &'a AccountInfo,
+    pub destination: &'a AccountInfo,
+    pub authority: &'a AccountInfo,
+    pub token_program: &'a Pubkey,
+}
+
+impl CloseAccount<'_> {
+    pub const DISCRIMINATOR: u8 = 9;
+
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let account_metas = [
+            AccountMeta::writable(self.account.key()),
+            AccountMeta::writable(self.destination.key()),
+            AccountMeta::readonly_signer(self.authority.key()),
+        ];
+
+        let instruction = Instruction {
+            program_id: self.token_program,
+            accounts: &account_metas,
+            data: &[Self::DISCRIMINATOR],
+        };
+
+        invoke_signed(
+            &instruction,
+            &[self.account, self.destination, self.authority],
+            signers,
+        )
+    }
+}
+
+pub struct InitializeMint2<'a> {
+    pub mint: &'a AccountInfo,
+    pub decimals: u8,
+    pub mint_authority: &'a Pubkey,
+    pub freeze_authority: Option<&'a Pubkey>,
+    pub token_program: &'a Pubkey,
+}
+
+impl InitializeMint2<'_> {
+    pub const DISCRIMINATOR: u8 = 20;
+
+    pub fn invoke(&self) -> ProgramResult {
+        let account_metas = [AccountMeta::writable(self.mint.key())];
+
+        // discriminator, decimals, mint authority, then an optional freeze authority
+        let mut data = [0u8; 67];
+        data[0] = Self::DISCRIMINATOR;
+        data[1] = self.decimals;
+        data[2..34].copy_from_slice(self.mint_authority);
+
+        if let Some(freeze_authority) = self.freeze_authority {
+            data[34] = 1;
+            data[35..67].copy_from_slice(freeze_authority);
+        }
+
+        let instruction = Instruction {
+            program_id: self.token_program,
+            accounts: &account_metas,
+            data: &data,
+        };
+
+        invoke_signed(&instruction, &[self.mint], &[])
+    }
+}